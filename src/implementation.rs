@@ -2,21 +2,35 @@ mod compound;
 mod dual_attr;
 mod main_field;
 mod opts;
+mod overrides;
+mod rename;
+mod runtime;
+mod template;
 mod variant;
 
 pub use compound::Alias;
 
-use main_field::MainField;
+use main_field::{member_name, MainField};
+use overrides::Overrides;
+use runtime::Runtime;
+use template::Template;
 use variant::{Style, Variant};
 
-use macroific::elements::module_prefix::RESULT;
+use macroific::elements::module_prefix::{OPTION, RESULT};
 use macroific::elements::{GenericImpl, ModulePrefix};
 use macroific::prelude::*;
-use opts::ContainerOptions;
+use opts::{
+    Alternate, Case, ContainerOptions, EscapeMode, Level, NoneStyle, QuoteStyle, Redact, UnitStyle,
+};
 use proc_macro::TokenStream as TokenStream1;
 use proc_macro2::{Ident, TokenStream};
 use quote::{quote, ToTokens};
-use syn::{parse_quote, Data, DeriveInput, Error, Generics};
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_quote, Data, DeriveInput, Error, GenericArgument, Generics, LitStr, Member,
+    PathArguments, Token, Type, WherePredicate,
+};
 
 const FMT: ModulePrefix<2> = ModulePrefix::new(["core", "fmt"]);
 
@@ -25,6 +39,7 @@ pub(crate) struct Implementation<'a> {
     ident: Ident,
     generics: Generics,
     opts: ContainerOptions,
+    runtime: Runtime,
 }
 
 impl<'a> Implementation<'a> {
@@ -48,10 +63,11 @@ impl<'a> Implementation<'a> {
         } = syn::parse(input)?;
 
         let common = Self {
-            opts: ContainerOptions::resolve(attrs, attr_name)?,
+            opts: ContainerOptions::resolve(attrs, attr_name, Level::container(&data))?,
             trait_name,
             ident,
             generics,
+            runtime: Runtime::default(),
         };
 
         common.exec_data(data, attr_name)
@@ -60,82 +76,128 @@ impl<'a> Implementation<'a> {
     fn exec_data(self, data: Data, attr_name: &str) -> syn::Result<TokenStream> {
         match data {
             Data::Struct(data) => {
-                let main_field = MainField::resolve_from_fields(data.fields, attr_name)?;
-                Ok(self.impl_struct(main_field))
+                self.check_quote(None, None)?;
+
+                if self.opts.literal.is_some() {
+                    return Ok(self.impl_struct(&[], None));
+                }
+
+                if let Some(fmt) = &self.opts.fmt {
+                    let template = Template::parse(fmt, &data.fields)?;
+                    return Ok(self.impl_struct(&[], Some(template)));
+                }
+
+                if self.opts.method.is_some() {
+                    return Ok(self.impl_struct(&[], None));
+                }
+
+                let multiple = self.opts.join.is_some() || self.opts.named;
+                let main_fields = MainField::resolve_from_fields(
+                    data.fields,
+                    attr_name,
+                    multiple,
+                    self.opts.alternate_field(),
+                    self.opts.selection(),
+                )?;
+
+                for main_field in &main_fields {
+                    self.check_quote(None, Some(main_field))?;
+                }
+
+                Ok(self.impl_struct(&main_fields, None))
             }
             Data::Enum(data) => {
                 let variants: Vec<Variant> = data
                     .variants
                     .into_iter()
-                    .map(move |v| Variant::from_syn(v, attr_name))
+                    .map(|v| Variant::from_syn(v, attr_name, &self.opts))
                     .collect::<syn::Result<_>>()?;
 
+                if let Some(selection) = self.opts.selection() {
+                    Self::check_selected_field(selection.field, &variants)?;
+                }
+
+                for variant in &variants {
+                    self.check_quote(Some(&variant.opts), None)?;
+                    for main_field in &variant.main_fields {
+                        self.check_quote(Some(&variant.opts), Some(main_field))?;
+                    }
+                }
+
                 Ok(self.impl_enum(variants))
             }
             Data::Union(u) => Err(Error::new_spanned(u.union_token, "Unions not supported")),
         }
     }
 
-    fn trait_path(&self) -> ModulePrefix<3> {
+    /// Error out on variants that had nothing to delegate to because they lack the field selected
+    /// through the container's `field` or `path` option
+    fn check_selected_field(field: &Member, variants: &[Variant]) -> syn::Result<()> {
+        let missing = variants
+            .iter()
+            .filter(move |v| v.lacks_selected_field)
+            .map(move |v| format!("`{}`", v.ident))
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let msg = format!(
+            "No field {} in variant(s) {}; mark the field to delegate to on them instead",
+            member_name(field),
+            missing.join(", "),
+        );
+
+        Err(Error::new_spanned(field, msg))
+    }
+
+    /// Error out on `quote = single` with JSON escaping: JSON strings can't be single-quoted &
+    /// single quotes can't be escaped within them
+    fn check_quote(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+    ) -> syn::Result<()> {
+        let escape = self.opt(variant_opts, main_field, |o| o.escape.as_ref());
+        let quote = self.opt(variant_opts, main_field, |o| o.quote.as_ref());
+
+        match (escape, quote) {
+            (Some(EscapeMode::Json), Some(&QuoteStyle::Single(span))) => Err(Error::new(
+                span,
+                "JSON strings can't be single-quoted; use `quote = double` or `quote = none`",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn trait_path(&self) -> ModulePrefix<'_, 3> {
         ModulePrefix::new(["core", "fmt", self.trait_name])
     }
 
+    /// The trait to delegate through: the `via` option or the trait being implemented
+    fn called_trait(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+    ) -> &'a str {
+        match self.opt(variant_opts, main_field, |o| o.via.as_ref()) {
+            Some(via) => via.0,
+            None => self.trait_name,
+        }
+    }
+
     fn impl_enum(mut self, variants: Vec<Variant>) -> TokenStream {
         self.preprocess_generics_enum(&variants);
         let mut tokens = self.header();
         let mut has_skipped_arms = false;
-        let trait_path = self.trait_path();
 
-        let arms = variants.into_iter()
+        let arms = variants
+            .into_iter()
             .filter_map(|variant| {
-                let Variant { ident, style, main_field } = variant;
-
-                match style {
-                    Style::Tuple => {
-                        let Some(main_field) = main_field else {
-                            has_skipped_arms = true;
-                            return None;
-                        };
-
-                        let args = main_field.args_for_tuple_enum();
-                        let ty = if let Some(delegate_to) = &self.opts.delegate_to {
-                            delegate_to
-                        } else {
-                            &main_field.ty
-                        };
-
-                        Some(quote! {
-                            Self::#ident(#(#args),*) => <#ty as #trait_path>::fmt(v, f),
-                        })
-                    },
-                    Style::Named => {
-                        let Some(main_field) = main_field else {
-                            has_skipped_arms = true;
-                            return None;
-                        };
-
-                        let dots = if main_field.num_fields > 1 {
-                            quote!(,..)
-                        } else {
-                            TokenStream::new()
-                        };
-
-                        let field_name = &main_field.ident;
-                        let ty = if let Some(delegate_to) = &self.opts.delegate_to {
-                            delegate_to
-                        } else {
-                            &main_field.ty
-                        };
-
-                        Some(quote! {
-                            Self::#ident { #field_name: v #dots } => <#ty as #trait_path>::fmt(v, f),
-                        })
-                    },
-                    Style::Unit => {
-                        has_skipped_arms = true;
-                        None
-                    },
-                }
+                let arm = self.enum_arm(variant);
+                has_skipped_arms |= arm.is_none();
+                arm
             })
             .collect::<TokenStream>();
 
@@ -146,9 +208,11 @@ impl<'a> Implementation<'a> {
         };
 
         let formatter_name = Ident::create(if arms.is_empty() { "_" } else { "f" });
+        let runtime = &self.runtime;
 
         tokens.extend(quote! {{
             fn fmt(&self, #formatter_name: &mut #FMT::Formatter<'_>) -> #FMT::Result {
+                #runtime
                 match self {
                     #arms
                     #other_arm
@@ -159,30 +223,758 @@ impl<'a> Implementation<'a> {
         tokens
     }
 
-    fn impl_struct(mut self, main_field: Option<MainField>) -> TokenStream {
-        self.preprocess_generics_struct(&main_field);
-        let mut tokens = self.header();
-        let trait_path = self.trait_path();
+    /// Render a `match` arm for the variant. [`None`] means the variant has empty output.
+    fn enum_arm(&self, variant: Variant) -> Option<TokenStream> {
+        let Variant {
+            ident,
+            name,
+            style,
+            main_fields,
+            template,
+            opts,
+            ..
+        } = variant;
+
+        if let Some(template) = template {
+            let pattern = template.pattern();
+            let body = self.pad(Some(&opts), template.render(false));
+
+            return Some(quote! {
+                Self::#ident { #pattern .. } => #body,
+            });
+        }
+
+        if let Some(literal) = self.literal(Some(&opts)) {
+            return Some(quote! {
+                Self::#ident { .. } => f.pad(#literal),
+            });
+        }
+
+        if let Some(method) = self.opt(Some(&opts), None, |o| o.method.as_ref()) {
+            let call = self.delegate_call(Some(&opts), None, &quote!(&self.#method()));
+            let body = self.pad(Some(&opts), self.variant_prefix(&opts, &name, call));
+
+            return Some(quote! {
+                Self::#ident { .. } => #body,
+            });
+        }
+
+        if main_fields.is_empty() {
+            let body = self.pad(Some(&opts), self.unit_body(Some(&opts), &name)?);
+
+            return Some(quote! {
+                Self::#ident { .. } => #body,
+            });
+        }
 
-        let (body, param) = if let Some(main_field) = main_field {
-            let ident = main_field.ident_for_struct();
-            let ty = if let Some(delegate_to) = &self.opts.delegate_to {
-                delegate_to
+        let body = self.delegate_body(Some(&opts), &name, &main_fields, false);
+        let body = self.pad(Some(&opts), self.variant_prefix(&opts, &name, body));
+
+        Some(match style {
+            Style::Tuple => {
+                let args = MainField::args_for_tuple_enum(&main_fields);
+                quote! {
+                    Self::#ident(#(#args),*) => #body,
+                }
+            }
+            Style::Named => {
+                let dots = if main_fields.len() < main_fields[0].num_fields {
+                    quote!(,..)
+                } else {
+                    TokenStream::new()
+                };
+
+                let field_names = main_fields.iter().map(move |f| &f.ident);
+                let bindings = main_fields.iter().map(MainField::binding);
+
+                quote! {
+                    Self::#ident { #(#field_names: #bindings),* #dots } => #body,
+                }
+            }
+            Style::Unit => unreachable!("Unit variants have no fields"),
+        })
+    }
+
+    /// Write the `variant_prefix`, with `{}` replaced by the variant's name, before the body
+    fn variant_prefix(
+        &self,
+        variant_opts: &ContainerOptions,
+        name: &str,
+        body: TokenStream,
+    ) -> TokenStream {
+        match self.opt(Some(variant_opts), None, |o| o.variant_prefix.as_ref()) {
+            Some(prefix) => {
+                let prefix = prefix.value().replace("{}", name);
+                quote!({
+                    f.write_str(#prefix)?;
+                    #body
+                })
+            }
+            None => body,
+        }
+    }
+
+    /// Delegate to the given fields, writing the `join` separator between them or, in `named`
+    /// mode, the struct/variant `name` around them. Delegates to the `alternate` field instead
+    /// when formatting with the `#` flag.
+    fn delegate_body(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        name: &str,
+        main_fields: &[MainField],
+        from_self: bool,
+    ) -> TokenStream {
+        let fields = main_fields.iter().filter(move |f| !f.alternate);
+        let body = if self.flag(variant_opts, None, |o| o.named) {
+            self.named_body(variant_opts, name, fields, from_self)
+        } else {
+            self.join_body(variant_opts, fields, from_self)
+        };
+
+        let Some(alternate) = main_fields.iter().find(move |f| f.alternate) else {
+            return body;
+        };
+
+        let alternate =
+            self.delegate_call(variant_opts, Some(alternate), &alternate.target(from_self));
+
+        quote! {
+            if f.alternate() {
+                #alternate
             } else {
-                &main_field.ty
+                #body
+            }
+        }
+    }
+
+    /// Write the fields through [`debug_tuple`](::core::fmt::Formatter::debug_tuple) or
+    /// [`debug_struct`](::core::fmt::Formatter::debug_struct), delegating for each field's value.
+    /// Omitted fields are indicated with `..`.
+    fn named_body<'f>(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        name: &str,
+        main_fields: impl Iterator<Item = &'f MainField>,
+        from_self: bool,
+    ) -> TokenStream {
+        let fmt_fn = self.runtime.fmt_fn();
+        let mut is_struct = false;
+        let mut num_written = 0;
+        let mut num_fields = 0;
+
+        let fields = main_fields
+            .map(|main_field| {
+                num_written += 1;
+                num_fields = main_field.num_fields;
+
+                let call = self.delegate_call(
+                    variant_opts,
+                    Some(main_field),
+                    &main_field.target(from_self),
+                );
+                let value = quote!(&#fmt_fn(|f: &mut #FMT::Formatter<'_>| #call));
+
+                if let Some(ident) = &main_field.ident {
+                    is_struct = true;
+                    let name = match &main_field.opts.rename {
+                        Some(rename) => rename.value(),
+                        None => ident.unraw().to_string(),
+                    };
+                    quote!(.field(#name, #value))
+                } else {
+                    quote!(.field(#value))
+                }
+            })
+            .collect::<TokenStream>();
+
+        let finish = if num_written == num_fields {
+            quote!(.finish())
+        } else if is_struct {
+            quote!(.finish_non_exhaustive())
+        } else {
+            // `DebugTuple::finish_non_exhaustive` is newer than the MSRV
+            quote!(.field(&#fmt_fn(|f: &mut #FMT::Formatter<'_>| f.write_str(".."))).finish())
+        };
+
+        if is_struct {
+            quote!(f.debug_struct(#name) #fields #finish)
+        } else {
+            quote!(f.debug_tuple(#name) #fields #finish)
+        }
+    }
+
+    fn join_body<'f>(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_fields: impl Iterator<Item = &'f MainField>,
+        from_self: bool,
+    ) -> TokenStream {
+        let mut calls = main_fields.map(move |main_field| {
+            self.delegate_call(
+                variant_opts,
+                Some(main_field),
+                &main_field.target(from_self),
+            )
+        });
+
+        let Some(first) = calls.next() else {
+            return quote!(#RESULT::Ok(()));
+        };
+
+        let separator = self.opt(variant_opts, None, |o| o.join.as_ref());
+
+        let rest = calls
+            .map(move |call| {
+                quote! {
+                    ?;
+                    f.write_str(#separator)?;
+                    #call
+                }
+            })
+            .collect::<TokenStream>();
+
+        if rest.is_empty() {
+            first
+        } else {
+            quote!({ #first #rest })
+        }
+    }
+
+    /// Look up an option, giving field options precedence over variant options and variant options
+    /// precedence over container options.
+    fn opt<'t, T: ?Sized>(
+        &'t self,
+        variant_opts: Option<&'t ContainerOptions>,
+        main_field: Option<&'t MainField>,
+        get: fn(&ContainerOptions) -> Option<&T>,
+    ) -> Option<&'t T> {
+        main_field
+            .and_then(move |f| get(&f.opts))
+            .or_else(move || get(variant_opts?))
+            .or_else(move || get(&self.opts))
+    }
+
+    /// Format a reference to a delegation target, unwrapping it first if it's an [`Option`]
+    fn delegate_call(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        value: &TokenStream,
+    ) -> TokenStream {
+        let redact = self.opt(variant_opts, main_field, |o| o.redact.as_ref());
+
+        // A bare mask doesn't look at the value, so there's nothing to unwrap
+        let unwrap = self.is_option(variant_opts, main_field)
+            && redact.map_or(true, move |Redact(r)| r.prefix.is_some() || r.len);
+
+        if !unwrap {
+            return self.unwrapped_call(variant_opts, main_field, value, redact);
+        }
+
+        let call = self.unwrapped_call(variant_opts, main_field, &quote!(__dd_value), redact);
+        let none = match self.opt(variant_opts, main_field, |o| o.none.as_ref()) {
+            Some(NoneStyle::Empty) | None => quote!(#RESULT::Ok(())),
+            Some(NoneStyle::Error) => quote!(#RESULT::Err(#FMT::Error)),
+            Some(NoneStyle::Text(text)) => quote!(f.pad(#text)),
+        };
+
+        quote! {
+            match #value {
+                #OPTION::Some(__dd_value) => #call,
+                #OPTION::None => #none,
+            }
+        }
+    }
+
+    /// Format a reference to a delegation target that's already been unwrapped from its
+    /// [`Option`], if it was in one
+    fn unwrapped_call(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        value: &TokenStream,
+        redact: Option<&Redact>,
+    ) -> TokenStream {
+        if let Some(redact) = redact {
+            let body = self.redact_body(variant_opts, main_field, value, redact);
+            return self.decorate(variant_opts, main_field, body);
+        }
+
+        let call = self.target_call(variant_opts, main_field, value);
+        let call = self.override_flags(variant_opts, main_field, &call);
+        let call = self.truncate(variant_opts, main_field, &call);
+        let call = self.convert_case(variant_opts, main_field, &call);
+        let call = self.escape(variant_opts, main_field, &call);
+        self.decorate(variant_opts, main_field, call)
+    }
+
+    /// Write the `redact` output in place of the delegation target. Only revealing a prefix
+    /// actually formats it.
+    fn redact_body(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        value: &TokenStream,
+        Redact(redact): &Redact,
+    ) -> TokenStream {
+        let mask = if redact.type_name {
+            self.redacted_type_name(variant_opts, main_field, value)
+        } else {
+            let mask = redact
+                .mask
+                .as_ref()
+                .map_or_else(|| "***".into(), LitStr::value);
+            quote!(#mask)
+        };
+
+        if redact.prefix.is_none() && !redact.len {
+            return quote!(f.pad(#mask));
+        }
+
+        let (inner, prefix) = match redact.prefix {
+            Some(prefix) => {
+                let limit = self.runtime.limit();
+                let fmt_fn = self.runtime.fmt_fn();
+                let call = self.fmt_call(variant_opts, main_field, value);
+
+                (
+                    Some(quote! {
+                        let flags = __dd_flags(f);
+                        let inner = #fmt_fn(|f: &mut #FMT::Formatter<'_>| #call);
+                    }),
+                    Some(quote!(#limit(out, flags, #prefix, &inner)?;)),
+                )
+            }
+            None => (None, None),
+        };
+
+        let len = if redact.len {
+            quote!(::core::write!(out, " (len {})", (#value).len()))
+        } else {
+            quote!(#RESULT::Ok(()))
+        };
+
+        // Padded as a whole, same as the bare mask
+        let measure = self.runtime.measure();
+        quote! {{
+            #inner
+            #measure(f, &|out: &mut dyn #FMT::Write| {
+                #prefix
+                out.write_str(#mask)?;
+                #len
+            })
+        }}
+    }
+
+    /// The name `redact(type_name)` writes: the container's if the option was set on the
+    /// container or the redacted value's type's if it was set on a variant or field
+    fn redacted_type_name(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        value: &TokenStream,
+    ) -> TokenStream {
+        let from_container = main_field.map_or(true, move |f| f.opts.redact.is_none())
+            && variant_opts.map_or(true, move |o| o.redact.is_none());
+
+        if from_container {
+            let name = self.ident.unraw().to_string();
+            return quote!(#name);
+        }
+
+        // `Option`s only get unwrapped for `Display`-like traits, but the secret is what's inside
+        let ty = main_field
+            .filter(move |f| f.is_direct())
+            .and_then(MainField::option_inner)
+            .or_else(move || self.target_ty(variant_opts, main_field));
+
+        if let Some(Type::Path(ty)) = ty {
+            if let Some(segment) = ty.path.segments.last() {
+                let name = segment.ident.unraw().to_string();
+                return quote!(#name);
+            }
+        }
+
+        // The type can't be seen by the macro, e.g. because it's a method's return value
+        quote! {{
+            fn __dd_type_name<T: ?::core::marker::Sized>(_: &T) -> &'static str {
+                ::core::any::type_name::<T>()
+            }
+            __dd_type_name(#value)
+        }}
+    }
+
+    /// Format the (unwrapped) delegation target, iterating over it if the `iter` flag is set
+    fn target_call(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        value: &TokenStream,
+    ) -> TokenStream {
+        if !self.flag(variant_opts, main_field, |o| o.iter) {
+            return self.fmt_call(variant_opts, main_field, value);
+        }
+
+        let sep = self
+            .opt(variant_opts, main_field, |o| o.sep.as_ref())
+            .map_or_else(move || quote!(", "), ToTokens::to_token_stream);
+        let open = self
+            .opt(variant_opts, main_field, |o| o.open.as_ref())
+            .map(move |open| quote!(f.write_str(#open)?;));
+        let close = match self.opt(variant_opts, main_field, |o| o.close.as_ref()) {
+            Some(close) => quote!(f.write_str(#close)),
+            None => quote!(#RESULT::Ok(())),
+        };
+
+        let (pattern, item) = match self.opt(variant_opts, main_field, |o| o.kv_sep.as_ref()) {
+            Some(kv_sep) => {
+                let key = self.fmt_call(variant_opts, main_field, &quote!(__dd_key));
+                let value = self.fmt_call(variant_opts, main_field, &quote!(__dd_value));
+                let item = quote! {
+                    #key?;
+                    f.write_str(#kv_sep)?;
+                    #value?;
+                };
+
+                (quote!((__dd_key, __dd_value)), item)
+            }
+            None => {
+                let item = self.fmt_call(variant_opts, main_field, &quote!(&__dd_item));
+                (quote!(__dd_item), quote!(#item?;))
+            }
+        };
+
+        // `&&[T]` isn't iterable, but `&[T]` is
+        let iterable = match self.target_ty(variant_opts, main_field) {
+            Some(Type::Reference(r)) if r.mutability.is_none() => quote!(*#value),
+            _ => value.clone(),
+        };
+
+        quote! {{
+            #open
+            for (__dd_idx, #pattern) in ::core::iter::Iterator::enumerate(::core::iter::IntoIterator::into_iter(#iterable)) {
+                if __dd_idx != 0 {
+                    f.write_str(#sep)?;
+                }
+                #item
+            }
+            #close
+        }}
+    }
+
+    /// Check a boolean option, which is set if it's set on the field, the variant or the container
+    fn flag(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        get: fn(&ContainerOptions) -> bool,
+    ) -> bool {
+        get(&self.opts)
+            || variant_opts.is_some_and(get)
+            || main_field.is_some_and(move |f| get(&f.opts))
+    }
+
+    /// Whether the delegation target is an [`Option`] whose inner value should be delegated to.
+    /// Inferred from the field's type unless the `option` flag is set; [`Debug`](::core::fmt::Debug)
+    /// never gets inferred as [`Option`] implements it already.
+    fn is_option(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+    ) -> bool {
+        if self.flag(variant_opts, main_field, |o| o.option) {
+            return true;
+        }
+
+        main_field.is_some_and(move |f| f.is_direct() && f.option_inner().is_some())
+            && self
+                .opt(variant_opts, main_field, |o| o.with.as_ref())
+                .is_none()
+            && self.called_trait(variant_opts, main_field) != "Debug"
+    }
+
+    /// Format `value` through the `with` function, the `delegate_to` type or, failing those, the
+    /// field's type. If there's no field or the field has a `method` option, the type gets inferred.
+    fn fmt_call(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        value: &TokenStream,
+    ) -> TokenStream {
+        if let Some(with) = self.opt(variant_opts, main_field, |o| o.with.as_ref()) {
+            return quote!(#with(#value, f));
+        }
+
+        let trait_path =
+            ModulePrefix::new(["core", "fmt", self.called_trait(variant_opts, main_field)]);
+
+        match self.delegate_ty(variant_opts, main_field) {
+            Some(ty) => quote!(<#ty as #trait_path>::fmt(#value, f)),
+            None => quote!(#trait_path::fmt(#value, f)),
+        }
+    }
+
+    /// Cut the output of a formatting call off after `max_len` characters, writing the
+    /// `ellipsis` if anything got cut
+    fn truncate(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        call: &TokenStream,
+    ) -> TokenStream {
+        let Some(max_len) = self.opt(variant_opts, main_field, |o| o.max_len.as_ref()) else {
+            return call.clone();
+        };
+
+        let ellipsis = self
+            .opt(variant_opts, main_field, |o| o.ellipsis.as_ref())
+            .map_or_else(move || "\u{2026}".into(), LitStr::value);
+        let truncate = self.runtime.truncate();
+        let fmt_fn = self.runtime.fmt_fn();
+
+        quote! {
+            #truncate(f, #max_len, #ellipsis, &#fmt_fn(|f: &mut #FMT::Formatter<'_>| #call))
+        }
+    }
+
+    /// Run a formatting call with the `width`, `fill`, `align`, `alternate`, `sign_plus` &
+    /// `precision` overrides applied to its formatter
+    fn override_flags(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        call: &TokenStream,
+    ) -> TokenStream {
+        let overrides = Overrides {
+            width: self
+                .opt(variant_opts, main_field, |o| o.width.as_ref())
+                .copied(),
+            fill: self
+                .opt(variant_opts, main_field, |o| o.fill.as_ref())
+                .copied(),
+            align: self
+                .opt(variant_opts, main_field, |o| o.align.as_ref())
+                .copied(),
+            alternate: self.flag(variant_opts, main_field, |o| {
+                matches!(o.alternate, Some(Alternate::Flag))
+            }),
+            sign_plus: self.flag(variant_opts, main_field, |o| o.sign_plus),
+            precision: self
+                .opt(variant_opts, main_field, |o| o.precision.as_ref())
+                .copied(),
+            fallback: self.flag(variant_opts, main_field, |o| o.fallback),
+        };
+
+        if overrides.is_empty() {
+            call.clone()
+        } else {
+            overrides.wrap(&self.runtime, call)
+        }
+    }
+
+    /// Convert the output of a formatting call to the `case` set, if any
+    fn convert_case(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        call: &TokenStream,
+    ) -> TokenStream {
+        let Some(case) = self.opt(variant_opts, main_field, |o| o.case.as_ref()) else {
+            return call.clone();
+        };
+
+        let upper = matches!(case, Case::Upper);
+        let convert = self.runtime.case();
+        let measure = self.runtime.measure();
+        let fmt_fn = self.runtime.fmt_fn();
+
+        quote! {{
+            let flags = __dd_flags(f);
+            let inner = #fmt_fn(|f: &mut #FMT::Formatter<'_>| #call);
+            #measure(f, &|out: &mut dyn #FMT::Write| #convert(out, flags, #upper, &inner))
+        }}
+    }
+
+    /// Stream the output of a formatting call through the `escape` writer, wrapping it in quotes
+    fn escape(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        call: &TokenStream,
+    ) -> TokenStream {
+        let Some(&mode) = self.opt(variant_opts, main_field, |o| o.escape.as_ref()) else {
+            return call.clone();
+        };
+
+        let quote_style = self.opt(variant_opts, main_field, |o| o.quote.as_ref());
+        let quote_char = match quote_style
+            .copied()
+            .unwrap_or_else(move || mode.default_quote())
+        {
+            QuoteStyle::None => None,
+            QuoteStyle::Single(_) => Some('\''),
+            QuoteStyle::Double => Some('"'),
+        };
+
+        let (writer, escape) = self.runtime.escape(mode);
+        let measure = self.runtime.measure();
+        let fmt_fn = self.runtime.fmt_fn();
+        let write_quote = quote_char.map(move |c| quote!(out.write_char(#c)?;));
+        let quote_arg = match quote_char {
+            Some(c) => quote!(#OPTION::Some(#c)),
+            None => quote!(#OPTION::None),
+        };
+
+        quote! {{
+            let flags = __dd_flags(f);
+            let inner = #fmt_fn(|f: &mut #FMT::Formatter<'_>| #call);
+            #measure(f, &|out: &mut dyn #FMT::Write| {
+                #write_quote
+                __dd_forward(
+                    &mut #writer { out: &mut *out, escape: #escape, quote: #quote_arg },
+                    flags,
+                    &inner,
+                )?;
+                #write_quote
+                #RESULT::Ok(())
+            })
+        }}
+    }
+
+    /// Wrap a formatting call in the `prefix` & `suffix`, if any are set
+    fn decorate(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: Option<&MainField>,
+        call: TokenStream,
+    ) -> TokenStream {
+        let prefix = self.opt(variant_opts, main_field, |o| o.prefix.as_ref());
+        let suffix = self.opt(variant_opts, main_field, |o| o.suffix.as_ref());
+        if prefix.is_none() && suffix.is_none() {
+            return match main_field {
+                Some(main_field) if main_field.opts.pad => self.pad_call(&call),
+                _ => call,
             };
+        }
+
+        let prefix = prefix.map_or_else(String::new, LitStr::value);
+        let suffix = suffix.map_or_else(String::new, LitStr::value);
+        let decorate = self.runtime.decorate();
+        let fmt_fn = self.runtime.fmt_fn();
+
+        quote! {
+            #decorate(f, #prefix, #suffix, &#fmt_fn(|f: &mut #FMT::Formatter<'_>| #call))
+        }
+    }
 
+    /// Pad the whole output of a struct or variant if `pad` is set on it or the container
+    fn pad(&self, variant_opts: Option<&ContainerOptions>, body: TokenStream) -> TokenStream {
+        if self.flag(variant_opts, None, |o| o.pad) {
+            self.pad_call(&body)
+        } else {
+            body
+        }
+    }
+
+    fn pad_call(&self, body: &TokenStream) -> TokenStream {
+        let pad = self.runtime.pad();
+        let fmt_fn = self.runtime.fmt_fn();
+
+        quote!(#pad(f, &#fmt_fn(|f: &mut #FMT::Formatter<'_>| #body)))
+    }
+
+    /// Resolve the type to delegate to. [`None`] means it's unknown & should be inferred.
+    fn delegate_ty<'t>(
+        &'t self,
+        variant_opts: Option<&'t ContainerOptions>,
+        main_field: Option<&'t MainField>,
+    ) -> Option<&'t Type> {
+        if let Some(ty) = self.opt(variant_opts, main_field, |o| o.delegate_to.as_ref()) {
+            Some(ty)
+        } else if self.flag(variant_opts, main_field, |o| o.iter) {
+            None
+        } else {
+            self.target_ty(variant_opts, main_field)
+        }
+    }
+
+    /// The type of the field's value once it's been unwrapped from its [`Option`]. [`None`] if it
+    /// can't be determined.
+    fn target_ty<'t>(
+        &'t self,
+        variant_opts: Option<&'t ContainerOptions>,
+        main_field: Option<&'t MainField>,
+    ) -> Option<&'t Type> {
+        let main_field = main_field.filter(move |f| f.is_direct())?;
+        if self.is_option(variant_opts, Some(main_field)) {
+            main_field.option_inner()
+        } else {
+            Some(&main_field.ty)
+        }
+    }
+
+    /// The struct's identifier as it should be output, i.e. with `rename_all` applied
+    fn struct_name(&self) -> String {
+        let ident = self.ident.unraw().to_string();
+        match self.opts.rename_all {
+            Some(rule) => rule.apply(&ident),
+            None => ident,
+        }
+    }
+
+    fn literal<'t>(&'t self, variant_opts: Option<&'t ContainerOptions>) -> Option<&'t LitStr> {
+        self.opt(variant_opts, None, |o| o.literal.as_ref())
+    }
+
+    /// Output for structs & variants with nothing to delegate to. [`None`] means empty output.
+    /// Defaults to the name in `named` mode.
+    fn unit_body(
+        &self,
+        variant_opts: Option<&ContainerOptions>,
+        name: &str,
+    ) -> Option<TokenStream> {
+        let style = match self.opt(variant_opts, None, |o| o.unit.as_ref()) {
+            Some(style) => *style,
+            None if self.flag(variant_opts, None, |o| o.named) => UnitStyle::Name,
+            None => UnitStyle::default(),
+        };
+
+        match style {
+            UnitStyle::Empty => None,
+            UnitStyle::Name => Some(quote!(f.write_str(#name))),
+        }
+    }
+
+    fn impl_struct(mut self, main_fields: &[MainField], template: Option<Template>) -> TokenStream {
+        self.preprocess_generics_struct(main_fields, template.as_ref());
+        let mut tokens = self.header();
+
+        let (body, param) = if let Some(template) = template {
+            (self.pad(None, template.render(true)), Ident::create("f"))
+        } else if let Some(literal) = self.literal(None) {
+            (quote!(f.pad(#literal)), Ident::create("f"))
+        } else if let Some(method) = &self.opts.method {
+            let call = self.delegate_call(None, None, &quote!(&self.#method()));
+            (self.pad(None, call), Ident::create("f"))
+        } else if !main_fields.is_empty() {
             (
-                quote!(<#ty as #trait_path>::fmt(&self.#ident, f)),
+                self.pad(
+                    None,
+                    self.delegate_body(None, &self.struct_name(), main_fields, true),
+                ),
                 Ident::create("f"),
             )
+        } else if let Some(body) = self.unit_body(None, &self.struct_name()) {
+            (self.pad(None, body), Ident::create("f"))
         } else {
             (quote!(#RESULT::Ok(())), Ident::create("_"))
         };
 
+        let runtime = &self.runtime;
         tokens.extend(quote! {{
             #[inline]
             fn fmt(&self, #param: &mut #FMT::Formatter<'_>) -> #FMT::Result {
+                #runtime
                 #body
             }
         }});
@@ -202,17 +994,23 @@ impl<'a> Implementation<'a> {
         }
     }
 
-    fn preprocess_generics_struct(&mut self, main_field: &Option<MainField>) {
+    fn preprocess_generics_struct(
+        &mut self,
+        main_fields: &[MainField],
+        template: Option<&Template>,
+    ) {
         if self.generics.params.is_empty() {
             return;
         }
 
-        if self.preprocess_generics_common() {
-            return;
+        let has_container_bounds = self.extend_container_bounds();
+
+        for main_field in main_fields {
+            self.add_field_predicates(None, main_field, has_container_bounds);
         }
 
-        if let Some(main_field) = main_field {
-            self.add_debug_clause(&main_field.ty);
+        if let Some(template) = template.filter(move |_| !has_container_bounds) {
+            self.add_template_predicates(template);
         }
     }
 
@@ -221,39 +1019,154 @@ impl<'a> Implementation<'a> {
             return;
         }
 
-        if self.preprocess_generics_common() {
-            return;
-        }
+        let has_container_bounds = self.extend_container_bounds();
 
         for variant in variants {
-            if let Some(main_field) = &variant.main_field {
-                self.add_debug_clause(&main_field.ty);
+            if let Some(template) = &variant.template {
+                if !variant.opts.bounds.is_empty() {
+                    self.extend_bounds(&variant.opts.bounds);
+                } else if !has_container_bounds {
+                    self.add_template_predicates(template);
+                }
+            } else if !variant.main_fields.is_empty() {
+                for main_field in &variant.main_fields {
+                    self.add_field_predicates(
+                        Some(&variant.opts),
+                        main_field,
+                        has_container_bounds,
+                    );
+                }
+            } else if !variant.opts.bounds.is_empty() {
+                self.extend_bounds(&variant.opts.bounds);
             }
         }
     }
 
-    fn preprocess_generics_common(&mut self) -> bool {
-        if !self.opts.bounds.is_empty() {
-            let iter = self.opts.bounds.iter().cloned();
-            self.generics.make_where_clause().predicates.extend(iter);
-            true
-        } else if let Some(delegate_to) = &self.opts.delegate_to {
-            self.add_debug_clause(delegate_to.clone());
+    /// Add the container's explicit bounds, if any. Returns `true` if there were any.
+    fn extend_container_bounds(&mut self) -> bool {
+        if self.opts.bounds.is_empty() {
+            false
+        } else {
+            let bounds = self.opts.bounds.clone();
+            self.extend_bounds(&bounds);
             true
+        }
+    }
+
+    /// Add the most specific explicit bounds set for the field, falling back to inferring them
+    /// from the delegated type when neither the field, the variant, nor the container set any.
+    /// Nothing gets inferred for fields formatted through a `with` function or redacted without
+    /// revealing a prefix.
+    fn add_field_predicates(
+        &mut self,
+        variant_opts: Option<&ContainerOptions>,
+        main_field: &MainField,
+        has_container_bounds: bool,
+    ) {
+        if !main_field.opts.bounds.is_empty() {
+            self.extend_bounds(&main_field.opts.bounds);
+        } else if let Some(variant_opts) = variant_opts.filter(|o| !o.bounds.is_empty()) {
+            self.extend_bounds(&variant_opts.bounds);
+        } else if !has_container_bounds
+            && self
+                .opt(variant_opts, Some(main_field), |o| o.with.as_ref())
+                .is_none()
+            && self
+                .opt(variant_opts, Some(main_field), |o| o.redact.as_ref())
+                .map_or(true, move |r| r.0.prefix.is_some())
+        {
+            let trait_name = self.called_trait(variant_opts, Some(main_field));
+
+            if let Some(ty) = self.delegate_ty(variant_opts, Some(main_field)) {
+                self.add_debug_clause(ty.clone(), trait_name);
+            } else if self.flag(variant_opts, Some(main_field), |o| o.iter) {
+                if let Some(ty) = self.target_ty(variant_opts, Some(main_field)) {
+                    let is_map = self
+                        .opt(variant_opts, Some(main_field), |o| o.kv_sep.as_ref())
+                        .is_some();
+                    self.add_iter_predicates(ty.clone(), is_map, trait_name);
+                }
+            }
+        }
+    }
+
+    /// Bound `&Field` to be iterable with its items implementing the trait. Map items are
+    /// `(&K, &V)` tuples, with `K` & `V` taken from the first two generics of the field's type.
+    /// Fields that are shared references get iterated over directly.
+    fn add_iter_predicates(&mut self, ty: Type, is_map: bool, trait_name: &str) {
+        let path = ModulePrefix::new(["core", "fmt", trait_name]);
+
+        let (hrtb, lifetime, iterable, target) = match ty {
+            Type::Reference(r) if r.mutability.is_none() => {
+                let target = (*r.elem).clone();
+                (None, r.lifetime.clone(), Type::Reference(r), target)
+            }
+            ty => (
+                Some(quote!(for<'__dd>)),
+                Some(parse_quote!('__dd)),
+                parse_quote!(&'__dd #ty),
+                ty,
+            ),
+        };
+
+        let predicates: Punctuated<WherePredicate, Token![,]> = if is_map {
+            let Some((key, value)) = map_generics(&target) else {
+                return;
+            };
+
+            parse_quote! {
+                #hrtb #iterable: ::core::iter::IntoIterator<Item = (&#lifetime #key, &#lifetime #value)>,
+                #key: #path,
+                #value: #path,
+            }
         } else {
-            false
+            parse_quote! {
+                #hrtb #iterable: ::core::iter::IntoIterator,
+                #hrtb <#iterable as ::core::iter::IntoIterator>::Item: #path,
+            }
+        };
+
+        self.extend_bounds(&predicates);
+    }
+
+    fn extend_bounds(&mut self, bounds: &Punctuated<WherePredicate, Token![,]>) {
+        let iter = bounds.iter().cloned();
+        self.generics.make_where_clause().predicates.extend(iter);
+    }
+
+    fn add_template_predicates(&mut self, template: &Template) {
+        for (ty, trait_name) in template.predicates() {
+            self.add_debug_clause(ty, trait_name);
         }
     }
 
-    fn add_debug_clause<T>(&mut self, ty: T)
+    fn add_debug_clause<T>(&mut self, ty: T, trait_name: &str)
     where
         T: ToTokens,
     {
         let predicate = {
-            let path = self.trait_path();
+            let path = ModulePrefix::new(["core", "fmt", trait_name]);
             parse_quote!(#ty: #path)
         };
 
         self.generics.make_where_clause().predicates.push(predicate);
     }
 }
+
+/// The first two type arguments of a map type, e.g. `K` & `V` in `HashMap<K, V, S>`
+fn map_generics(ty: &Type) -> Option<(&Type, &Type)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let PathArguments::AngleBracketed(args) = &path.path.segments.last()?.arguments else {
+        return None;
+    };
+
+    let mut types = args.args.iter().filter_map(move |arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+
+    Some((types.next()?, types.next()?))
+}
@@ -1,4 +1,5 @@
-use super::opts::MultiContainerOptions;
+use super::opts::{Level, MultiContainerOptions};
+use super::runtime::Runtime;
 use super::Implementation;
 use crate::ATTR_FMT;
 use macroific::prelude::*;
@@ -12,7 +13,7 @@ pub struct Alias<'a> {
     pub trait_name: &'a str,
 }
 
-impl<'a> Implementation<'a> {
+impl Implementation<'_> {
     pub fn exec_compound(input: TokenStream1) -> TokenStream1 {
         Self::exec_compound_2(input)
             .unwrap_or_else(Error::into_compile_error)
@@ -28,7 +29,18 @@ impl<'a> Implementation<'a> {
             ..
         } = syn::parse(input)?;
 
-        let tokens = MultiContainerOptions::from_iter_named(ATTR_FMT, Span::call_site(), attrs)?
+        let fmt_attrs = attrs
+            .into_iter()
+            .filter(move |a| a.path().is_ident(ATTR_FMT))
+            .collect::<Vec<_>>();
+        let opts = MultiContainerOptions::from_iter(Span::call_site(), fmt_attrs.iter().cloned())?;
+
+        let level = Level::container(&data);
+        for attr in &fmt_attrs {
+            level.check_compound(attr)?;
+        }
+
+        let tokens = opts
             .into_iter()
             .map(move |(alias, opts)| -> syn::Result<TokenStream> {
                 let for_alias = Self {
@@ -36,6 +48,7 @@ impl<'a> Implementation<'a> {
                     trait_name: alias.trait_name,
                     ident: ident.clone(),
                     generics: generics.clone(),
+                    runtime: Runtime::default(),
                 };
 
                 for_alias.exec_data(data.clone(), alias.attr_name)
@@ -15,17 +15,6 @@ pub(crate) enum AttrKind {
     Primary,
 }
 
-impl AttrKind {
-    pub fn aggregate<I>(attrs: I, attr_name: &str) -> Option<Self>
-    where
-        I: IntoIterator<Item = Attribute>,
-    {
-        DualAttr::collect(attrs, attr_name)
-            .last()
-            .map(move |a| a.attr_ty)
-    }
-}
-
 impl DualAttr {
     pub fn collect<I>(attrs: I, attr_name: &str) -> Vec<Self>
     where
@@ -1,19 +1,33 @@
-use super::dual_attr::AttrKind;
+use super::dual_attr::{AttrKind, DualAttr};
+use super::opts::{ContainerOptions, Level};
 use crate::ATTR_ANY;
 use macroific::prelude::*;
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::ToTokens;
+use quote::{format_ident, quote, ToTokens};
 use std::fmt::Display;
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{Error, LitInt, Type};
+use syn::{Attribute, Error, GenericArgument, LitInt, Member, PathArguments, Type};
 
 pub(crate) struct MainField {
     pub idx: usize,
     pub ident: Option<Ident>,
     pub ty: Type,
     pub num_fields: usize,
-    pub mark: Option<AttrKind>,
+    pub opts: ContainerOptions,
+
+    /// Delegated to instead of the other fields when formatting with the `#` flag
+    pub alternate: bool,
+
+    /// Fields to go through to get to the delegation target, e.g. `id.0` for `self.meta.id.0`
+    pub path: Vec<Member>,
+}
+
+/// The field to delegate to if none are marked, along with the path to go through within it
+#[derive(Copy, Clone)]
+pub(crate) struct Selection<'a> {
+    pub field: &'a Member,
+    pub path: &'a [Member],
 }
 
 impl MainField {
@@ -25,87 +39,251 @@ impl MainField {
         }
     }
 
-    pub fn args_for_tuple_enum(&self) -> impl Iterator<Item = Ident> {
-        fn empty_ident<T>(_: T) -> Ident {
-            Ident::create("_")
+    /// The variable the field gets bound to when destructuring an enum variant
+    pub fn binding(&self) -> Ident {
+        format_ident!("__dd_field{}", self.idx)
+    }
+
+    /// An expression referencing the field, or the end of its `path`: `&self.field` if
+    /// `from_self` is `true` or its [binding](Self::binding) otherwise.
+    pub fn accessor(&self, from_self: bool) -> TokenStream {
+        let path = &self.path;
+
+        if from_self {
+            let ident = self.ident_for_struct();
+            quote!(&self.#ident #(.#path)*)
+        } else if path.is_empty() {
+            self.binding().into_token_stream()
+        } else {
+            let binding = self.binding();
+            quote!(&#binding #(.#path)*)
         }
+    }
 
-        let pre = (0..self.idx).map(empty_ident);
-        let post = ((self.idx + 1)..self.num_fields).map(empty_ident);
+    /// A reference to the field's delegation target: the field itself or, if the field has a
+    /// `method` option, the method's return value.
+    pub fn target(&self, from_self: bool) -> TokenStream {
+        let Some(method) = &self.opts.method else {
+            return self.accessor(from_self);
+        };
 
-        pre.chain(Some(Ident::create("v"))).chain(post)
+        let path = &self.path;
+        if from_self {
+            let ident = self.ident_for_struct();
+            quote!(&self.#ident #(.#path)*.#method())
+        } else {
+            let binding = self.binding();
+            quote!(&#binding #(.#path)*.#method())
+        }
     }
 
-    pub fn resolve_from_fields(fields: syn::Fields, attr_name: &str) -> syn::Result<Option<Self>> {
-        match fields {
+    /// Whether the delegation target is the field itself rather than something reached through
+    /// a `path` or `method`
+    pub fn is_direct(&self) -> bool {
+        self.path.is_empty() && self.opts.method.is_none()
+    }
+
+    /// `T` if the field's type is spelled as `Option<T>`
+    pub fn option_inner(&self) -> Option<&Type> {
+        let Type::Path(path) = &self.ty else {
+            return None;
+        };
+
+        let segment = path
+            .path
+            .segments
+            .last()
+            .filter(move |s| s.ident == "Option")?;
+        let PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return None;
+        };
+
+        match args.args.first() {
+            Some(GenericArgument::Type(ty)) if args.args.len() == 1 => Some(ty),
+            _ => None,
+        }
+    }
+
+    /// Arguments for a tuple variant's pattern, binding the given fields and ignoring the rest
+    pub fn args_for_tuple_enum(fields: &[Self]) -> impl Iterator<Item = Ident> + '_ {
+        let num_fields = fields.first().map_or(0, move |f| f.num_fields);
+
+        (0..num_fields).map(move |idx| match fields.iter().find(move |f| f.idx == idx) {
+            Some(field) => field.binding(),
+            None => Ident::create("_"),
+        })
+    }
+
+    /// Resolve the fields of a struct. See [`resolve_from_iter`](Self::resolve_from_iter).
+    pub fn resolve_from_fields(
+        fields: syn::Fields,
+        attr_name: &str,
+        multiple: bool,
+        alternate: Option<&Member>,
+        selected: Option<Selection>,
+    ) -> syn::Result<Vec<Self>> {
+        if let Some(Selection { field: member, .. }) =
+            selected.filter(|s| !has_member(&fields, s.field))
+        {
+            let msg = format!("No field {} to delegate to", member_name(member));
+            return Err(Error::new_spanned(member, msg));
+        }
+
+        let span = fields.span();
+        let resolved = match fields {
             syn::Fields::Named(f) => {
-                let span = f.span();
-                Self::resolve_from_iter(f.named, attr_name, span)
+                Self::resolve_from_iter(f.named, attr_name, span, multiple, alternate, selected)?
             }
             syn::Fields::Unnamed(f) => {
-                let span = f.span();
-                Self::resolve_from_iter(f.unnamed, attr_name, span)
+                Self::resolve_from_iter(f.unnamed, attr_name, span, multiple, alternate, selected)?
             }
-            syn::Fields::Unit => Ok(None),
-        }
+            syn::Fields::Unit => Some(Vec::new()),
+        };
+
+        // Only `None` if the selected field is missing, which got checked above
+        Ok(resolved.unwrap_or_default())
     }
 
+    /// Resolve the fields to delegate to. Only one field can be marked unless `multiple` is
+    /// `true`; fields marked with the primary attribute take precedence over catch-all ones,
+    /// which take precedence over the `selected` field. The `alternate` field gets resolved
+    /// regardless of its marker & doesn't count towards them.
+    ///
+    /// [`None`] means there's nothing to delegate to because the `selected` field doesn't exist.
     pub fn resolve_from_iter<P>(
         fields: Punctuated<syn::Field, P>,
         attr_name: &str,
         span: Span,
-    ) -> syn::Result<Option<Self>> {
+        multiple: bool,
+        alternate: Option<&Member>,
+        selected: Option<Selection>,
+    ) -> syn::Result<Option<Vec<Self>>> {
         let num_fields = fields.len();
-        let mut fields = fields.into_iter().enumerate();
+        let mut first_field = None;
+        let mut selected_field = None;
+        let mut alternate_field = None;
+        let mut primary = Vec::new();
+        let mut catch_all = Vec::new();
+        let mut catch_all_dupe_span = None;
 
-        let mut first_field = match fields.next() {
-            Some((idx, field)) => Self {
+        for (idx, field) in fields.into_iter().enumerate() {
+            let span = field.span();
+            let (mark, opts) = parse_field_attrs(field.attrs, attr_name)?;
+            let is_alternate =
+                alternate.is_some_and(|member| is_member(member, field.ident.as_ref(), idx));
+            let is_selected =
+                selected.is_some_and(|s| is_member(s.field, field.ident.as_ref(), idx));
+            let path = opts
+                .path
+                .as_ref()
+                .map_or_else(Vec::new, move |p| p.0.clone());
+            let mut resolved = Self {
                 num_fields,
-                mark: AttrKind::aggregate(field.attrs, attr_name),
                 idx,
                 ident: field.ident,
                 ty: field.ty,
-            },
-            None => return Ok(None),
-        };
+                opts,
+                alternate: is_alternate,
+                path,
+            };
 
-        for (idx, field) in fields {
-            let span = field.span();
+            if is_alternate {
+                if let Some(selected) = selected.filter(|_| is_selected) {
+                    let msg = "The selected field can't also be the alternate";
+                    return Err(Error::new_spanned(selected.field, msg));
+                }
+
+                alternate_field = Some(resolved);
+                continue;
+            }
 
-            match AttrKind::aggregate(field.attrs, attr_name) {
-                None => {}
-                Some(AttrKind::Primary) => match first_field.mark {
-                    None | Some(AttrKind::CatchAll) => {
-                        first_field.update(AttrKind::Primary, idx, field.ident, field.ty);
+            match mark {
+                None if is_selected => {
+                    if let Some(selected) = selected {
+                        resolved.path.splice(0..0, selected.path.iter().cloned());
                     }
-                    Some(AttrKind::Primary) => return Err(duplicate_err(attr_name, span)),
-                },
-                Some(AttrKind::CatchAll) => match first_field.mark {
-                    None => {
-                        first_field.update(AttrKind::CatchAll, idx, field.ident, field.ty);
+                    selected_field = Some(resolved);
+                }
+                None => {
+                    first_field.get_or_insert(resolved);
+                }
+                Some(AttrKind::Primary) => {
+                    if !multiple && !primary.is_empty() {
+                        return Err(duplicate_err(attr_name, span));
                     }
-                    Some(AttrKind::Primary) => {}
-                    Some(AttrKind::CatchAll) => return Err(duplicate_err(ATTR_ANY, span)),
-                },
+                    primary.push(resolved);
+                }
+                Some(AttrKind::CatchAll) => {
+                    if !multiple && !catch_all.is_empty() {
+                        catch_all_dupe_span.get_or_insert(span);
+                    }
+                    catch_all.push(resolved);
+                }
             }
         }
 
-        if num_fields > 1 && first_field.mark.is_none() {
-            let msg = format!("At least one field must be marked with #[{attr_name}] or #[{ATTR_ANY}] on types with more than one field");
-            Err(Error::new(span, msg))
+        let mut out = if let Some(member) = alternate.filter(|_| alternate_field.is_none()) {
+            let msg = format!("No field {} to use as the alternate", member_name(member));
+            return Err(Error::new_spanned(member, msg));
+        } else if !primary.is_empty() {
+            primary
+        } else if let Some(span) = catch_all_dupe_span {
+            return Err(duplicate_err(ATTR_ANY, span));
+        } else if !catch_all.is_empty() {
+            catch_all
+        } else if let Some(selected_field) = selected_field {
+            vec![selected_field]
+        } else if num_fields - usize::from(alternate_field.is_some()) > 1 {
+            if selected.is_some() {
+                return Ok(None);
+            }
+
+            let msg = format!("At least one field must be marked with #[{attr_name}] or #[{ATTR_ANY}] or selected with `field` or `path` on types with more than one field");
+            return Err(Error::new(span, msg));
         } else {
-            Ok(Some(first_field))
-        }
+            first_field.into_iter().collect()
+        };
+
+        out.extend(alternate_field);
+        Ok(Some(out))
+    }
+}
+
+/// Whether the field at `idx` named `ident` is the one `member` refers to
+fn is_member(member: &Member, ident: Option<&Ident>, idx: usize) -> bool {
+    match member {
+        Member::Named(name) => ident == Some(name),
+        Member::Unnamed(index) => index.index as usize == idx,
     }
+}
+
+pub(crate) fn has_member(fields: &syn::Fields, member: &Member) -> bool {
+    fields
+        .iter()
+        .enumerate()
+        .any(move |(idx, f)| is_member(member, f.ident.as_ref(), idx))
+}
 
-    fn update(&mut self, mark: AttrKind, idx: usize, ident: Option<Ident>, ty: Type) {
-        self.mark = Some(mark);
-        self.idx = idx;
-        self.ident = ident;
-        self.ty = ty;
+/// How to refer to the field in error messages, e.g. ``named `foo` `` or `1`
+pub(crate) fn member_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => format!("named `{ident}`"),
+        Member::Unnamed(index) => index.index.to_string(),
     }
 }
 
+/// Get the field's marker kind along with any options provided through it
+fn parse_field_attrs(
+    attrs: Vec<Attribute>,
+    attr_name: &str,
+) -> syn::Result<(Option<AttrKind>, ContainerOptions)> {
+    let attrs = DualAttr::collect(attrs, attr_name);
+    let mark = attrs.last().map(move |a| a.attr_ty);
+    let opts = ContainerOptions::from_dual_attrs(attrs, Level::Field)?;
+
+    Ok((mark, opts))
+}
+
 fn duplicate_err<T>(attr_name: T, span: Span) -> Error
 where
     T: Display,
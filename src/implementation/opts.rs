@@ -1,15 +1,178 @@
 use super::compound::Alias;
 use super::dual_attr::DualAttr;
+use super::main_field::Selection;
+use super::rename::RenameRule;
 use macroific::prelude::*;
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::quote;
 use std::iter::FusedIterator;
+use std::mem;
 use std::ops::{Add, AddAssign};
+use syn::parse::{ParseStream, Parser};
 use syn::punctuated::Punctuated;
-use syn::{Attribute, Token, Type, WherePredicate};
+use syn::{
+    Attribute, Data, Error, Expr, ExprLit, Index, Lit, LitStr, Member, Meta, Path, Token, Type,
+    WherePredicate,
+};
 
 #[derive(AttributeOptions, ParseOption, Default, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub(crate) struct ContainerOptions {
     pub bounds: Punctuated<WherePredicate, Token![,]>,
     pub delegate_to: Option<Type>,
+    pub unit: Option<UnitStyle>,
+    pub literal: Option<LitStr>,
+    pub rename_all: Option<RenameRule>,
+    pub fmt: Option<LitStr>,
+    pub join: Option<LitStr>,
+    pub with: Option<Path>,
+    pub method: Option<Ident>,
+    pub via: Option<FmtTrait>,
+    pub prefix: Option<LitStr>,
+    pub suffix: Option<LitStr>,
+    pub pad: bool,
+    pub option: bool,
+    pub none: Option<NoneStyle>,
+    pub iter: bool,
+    pub sep: Option<LitStr>,
+    pub open: Option<LitStr>,
+    pub close: Option<LitStr>,
+    pub kv_sep: Option<LitStr>,
+    pub redact: Option<Redact>,
+    pub max_len: Option<usize>,
+    pub ellipsis: Option<LitStr>,
+    pub escape: Option<EscapeMode>,
+    pub quote: Option<QuoteStyle>,
+    pub case: Option<Case>,
+    pub width: Option<usize>,
+    pub fill: Option<char>,
+    pub align: Option<Align>,
+    pub alternate: Option<Alternate>,
+    pub sign_plus: bool,
+    pub precision: Option<usize>,
+    pub fallback: bool,
+    pub named: bool,
+    pub rename: Option<LitStr>,
+    pub variant_prefix: Option<LitStr>,
+    pub field: Option<FieldMember>,
+    pub path: Option<FieldPath>,
+}
+
+/// A [`fmt`](::core::fmt) trait to call instead of the one being derived.
+#[derive(Copy, Clone)]
+pub(crate) struct FmtTrait(pub &'static str);
+
+/// What to output for structs & enum variants that have no fields to delegate to.
+#[derive(Copy, Clone, Default)]
+pub(crate) enum UnitStyle {
+    /// Write nothing
+    #[default]
+    Empty,
+
+    /// Write the struct/variant identifier
+    Name,
+}
+
+/// Output to write instead of delegating, e.g. for secrets.
+#[derive(Clone)]
+pub(crate) struct Redact(pub RedactOptions);
+
+/// The `redact(...)` form of [`Redact`].
+#[derive(ParseOption, Default, Clone)]
+pub(crate) struct RedactOptions {
+    /// Defaults to `***`
+    pub mask: Option<LitStr>,
+
+    /// Write the type's name instead of the mask
+    pub type_name: bool,
+
+    /// Append the `len()` of the delegation target
+    pub len: bool,
+
+    /// Reveal the first N characters of the delegated output before the mask
+    pub prefix: Option<usize>,
+}
+
+/// Escaping to apply to the delegated output.
+#[derive(Copy, Clone)]
+pub(crate) enum EscapeMode {
+    /// Escape `&`, `<`, `>`, `"` & `'` as HTML entities
+    Html,
+
+    /// Escape the output as the contents of a JSON string
+    Json,
+
+    /// Escape the output as a POSIX shell word
+    Shell,
+
+    /// Escape the output like [`str`]'s [`Debug`](::core::fmt::Debug) implementation
+    Debug,
+}
+
+/// Case to convert the delegated output to.
+#[derive(Copy, Clone)]
+pub(crate) enum Case {
+    Lower,
+    Upper,
+}
+
+/// Alignment to pad the delegated output with when overriding its `width`.
+#[derive(Copy, Clone)]
+pub(crate) enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// A field referenced by name or, for tuple structs & variants, by index.
+#[derive(Clone)]
+pub(crate) struct FieldMember(pub Member);
+
+/// A `.`-separated path of fields, e.g. `meta.id.0`.
+#[derive(Clone)]
+pub(crate) struct FieldPath(pub Vec<Member>);
+
+/// The `alternate` option.
+#[derive(Clone)]
+pub(crate) enum Alternate {
+    /// Force the `#` flag on the delegated call
+    Flag,
+
+    /// Delegate to this field instead when formatting with the `#` flag
+    Field(Member),
+}
+
+/// Quotes to wrap escaped output in.
+#[derive(Copy, Clone)]
+pub(crate) enum QuoteStyle {
+    None,
+
+    /// Single quotes, along with where they were requested for error reporting
+    Single(Span),
+    Double,
+}
+
+/// What to output for [`None`] when delegating to an [`Option`].
+#[derive(Clone, Default)]
+pub(crate) enum NoneStyle {
+    /// Write nothing
+    #[default]
+    Empty,
+
+    /// Fail with a [`fmt::Error`](::core::fmt::Error)
+    Error,
+
+    /// Write the given placeholder
+    Text(LitStr),
+}
+
+/// Where a set of options was declared.
+#[derive(Copy, Clone)]
+pub(crate) enum Level {
+    Struct,
+    Enum,
+    Variant,
+    Field,
 }
 
 #[derive(AttributeOptions, Default)]
@@ -27,39 +190,219 @@ pub(crate) struct MultiContainerOptions {
 }
 
 impl ContainerOptions {
-    pub fn resolve<I>(attrs: I, attr_name: &str) -> syn::Result<Self>
+    pub fn resolve<I>(attrs: I, attr_name: &str, level: Level) -> syn::Result<Self>
     where
         I: IntoIterator<Item = Attribute>,
     {
-        let attrs = DualAttr::collect(attrs, attr_name);
+        Self::from_dual_attrs(DualAttr::collect(attrs, attr_name), level)
+    }
+
+    /// Merge the options of pre-sorted attributes. Attributes without arguments, e.g. field
+    /// markers, contribute nothing.
+    pub fn from_dual_attrs(attrs: Vec<DualAttr>, level: Level) -> syn::Result<Self> {
         let mut out = Self::default();
 
         for dattr in attrs {
-            let opts = ContainerOptions::from_attr(dattr.attr)?;
+            if let Meta::Path(_) = dattr.attr.meta {
+                continue;
+            }
+
+            let attr = expand_literal_shorthand(dattr.attr);
+            let opts = ContainerOptions::from_attr(attr.clone())?;
+            level.check(&attr, &opts)?;
             out += opts;
         }
 
         Ok(out)
     }
+
+    /// The field to delegate to if none are marked: the `field` option or the first field in the
+    /// `path`, along with the rest of the path
+    pub fn selection(&self) -> Option<Selection<'_>> {
+        if let Some(FieldMember(field)) = &self.field {
+            return Some(Selection { field, path: &[] });
+        }
+
+        let (field, path) = self.path.as_ref()?.0.split_first()?;
+        Some(Selection { field, path })
+    }
+
+    /// The field set through `alternate = field`
+    pub fn alternate_field(&self) -> Option<&Member> {
+        match &self.alternate {
+            Some(Alternate::Field(member)) => Some(member),
+            Some(Alternate::Flag) | None => None,
+        }
+    }
+}
+
+impl Level {
+    /// The level of a container's options
+    pub fn container(data: &Data) -> Self {
+        match data {
+            Data::Enum(_) => Self::Enum,
+            Data::Struct(_) | Data::Union(_) => Self::Struct,
+        }
+    }
+
+    /// Options that have no effect at this level
+    fn unsupported(self) -> &'static [&'static str] {
+        match self {
+            Self::Struct => &["variant_prefix", "rename"],
+            Self::Enum => &["rename"],
+            Self::Variant => &["rename_all", "rename"],
+            Self::Field => &[
+                "unit",
+                "literal",
+                "rename_all",
+                "fmt",
+                "join",
+                "named",
+                "variant_prefix",
+                "field",
+            ],
+        }
+    }
+
+    /// Error out on options in an already parsed attribute that have no effect at this level
+    fn check(self, attr: &Attribute, opts: &ContainerOptions) -> syn::Result<()> {
+        if let (Self::Field, Some(member)) = (self, opts.alternate_field()) {
+            let msg =
+                "`alternate = field` has no effect on a field; set it on the struct or variant";
+            return Err(Error::new_spanned(member, msg));
+        }
+
+        match &attr.meta {
+            Meta::List(list) => self.check_options(list.tokens.clone()),
+            Meta::Path(_) | Meta::NameValue(_) => Ok(()),
+        }
+    }
+
+    /// Error out on options in an already parsed `#[dfmt(...)]` attribute that have no effect at
+    /// this level
+    pub fn check_compound(self, attr: &Attribute) -> syn::Result<()> {
+        attr.parse_nested_meta(move |meta| {
+            if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                self.check_options(content.parse()?)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Error out on options in an already parsed option list that have no effect at this level
+    fn check_options(self, tokens: TokenStream) -> syn::Result<()> {
+        let level = match self {
+            Self::Struct => "a struct",
+            Self::Enum => "an enum",
+            Self::Variant => "a variant",
+            Self::Field => "a field",
+        };
+        let unsupported = self.unsupported();
+
+        let parser = syn::meta::parser(move |meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                if unsupported.iter().any(move |opt| ident == opt) {
+                    return Err(meta.error(format!("`{ident}` has no effect on {level}")));
+                }
+            }
+
+            // The values were already validated when parsing the options; skip over them
+            if meta.input.peek(syn::token::Paren) {
+                meta.input.parse::<TokenTree>()?;
+            } else if meta.path.is_ident("delegate_to") {
+                meta.value()?.parse::<Type>()?;
+            } else if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            }
+
+            Ok(())
+        });
+
+        parser.parse2(tokens)
+    }
+}
+
+/// Rewrite `#[attr("some literal", ...)]` into `#[attr(literal = "some literal", ...)]`
+fn expand_literal_shorthand(mut attr: Attribute) -> Attribute {
+    if let Meta::List(ref mut list) = attr.meta {
+        let mut tokens = mem::take(&mut list.tokens).into_iter().peekable();
+
+        if let Some(TokenTree::Literal(lit)) = tokens.peek() {
+            if let Lit::Str(lit) = Lit::new(lit.clone()) {
+                tokens.next();
+                let option = Ident::new("literal", lit.span());
+                list.tokens = quote!(#option = #lit);
+            }
+        }
+
+        list.tokens.extend(tokens);
+    }
+
+    attr
 }
 
 impl AddAssign for ContainerOptions {
     fn add_assign(&mut self, rhs: Self) {
-        let Self {
-            bounds: bounds_l,
-            delegate_to: delegate_to_l,
-        } = self;
+        // Destructuring `rhs` exhaustively makes sure no option gets left out
+        macro_rules! merge {
+            ([$($replace: ident),+ $(,)?], [$($flag: ident),+ $(,)?] $(,)?) => {
+                let Self { bounds, $($replace,)+ $($flag,)+ } = rhs;
 
-        let Self {
-            bounds: bounds_r,
-            delegate_to: delegate_to_r,
-        } = rhs;
+                self.bounds.extend(bounds);
 
-        bounds_l.extend(bounds_r);
+                $(
+                    if $replace.is_some() {
+                        self.$replace = $replace;
+                    }
+                )+
 
-        if let Some(delegate_to) = delegate_to_r {
-            *delegate_to_l = Some(delegate_to);
+                $(
+                    if $flag {
+                        self.$flag = true;
+                    }
+                )+
+            };
         }
+
+        merge!(
+            [
+                delegate_to,
+                unit,
+                literal,
+                rename_all,
+                fmt,
+                join,
+                with,
+                method,
+                via,
+                prefix,
+                suffix,
+                none,
+                sep,
+                open,
+                close,
+                kv_sep,
+                redact,
+                max_len,
+                ellipsis,
+                escape,
+                quote,
+                case,
+                width,
+                fill,
+                align,
+                alternate,
+                precision,
+                rename,
+                variant_prefix,
+                field,
+                path
+            ],
+            [pad, option, iter, sign_plus, fallback, named],
+        );
     }
 }
 
@@ -103,3 +446,303 @@ impl MultiContainerOptions {
         options.flatten()
     }
 }
+
+impl UnitStyle {
+    fn from_ident(ident: &Ident) -> syn::Result<Self> {
+        match ident.to_string().as_str() {
+            "empty" => Ok(Self::Empty),
+            "name" => Ok(Self::Name),
+            _ => Err(Error::new_spanned(ident, "Expected `empty` or `name`")),
+        }
+    }
+}
+
+impl ParseOption for UnitStyle {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_ident(&Ident::from_stream(input)?)
+    }
+}
+
+impl FromExpr for UnitStyle {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        Self::from_ident(&Ident::from_expr(expr)?)
+    }
+}
+
+impl NoneStyle {
+    fn from_ident(ident: &Ident) -> syn::Result<Self> {
+        match ident.to_string().as_str() {
+            "empty" => Ok(Self::Empty),
+            "error" => Ok(Self::Error),
+            _ => Err(Error::new_spanned(
+                ident,
+                "Expected `empty`, `error` or a string literal",
+            )),
+        }
+    }
+}
+
+impl ParseOption for NoneStyle {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_expr(Expr::from_stream(input)?)
+    }
+}
+
+impl FromExpr for NoneStyle {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        if let Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) = expr
+        {
+            Ok(Self::Text(lit))
+        } else {
+            Self::from_ident(&Ident::from_expr(expr)?)
+        }
+    }
+}
+
+impl EscapeMode {
+    /// The quotes to use if the `quote` option isn't set
+    pub fn default_quote(self) -> QuoteStyle {
+        match self {
+            Self::Html => QuoteStyle::None,
+            Self::Json | Self::Debug => QuoteStyle::Double,
+            Self::Shell => QuoteStyle::Single(Span::call_site()),
+        }
+    }
+
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "html" => Ok(Self::Html),
+            "json" => Ok(Self::Json),
+            "shell" => Ok(Self::Shell),
+            "debug" => Ok(Self::Debug),
+            _ => Err(Error::new_spanned(
+                lit,
+                "Expected one of \"html\", \"json\", \"shell\", \"debug\"",
+            )),
+        }
+    }
+}
+
+impl ParseOption for EscapeMode {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_lit(&LitStr::from_stream(input)?)
+    }
+}
+
+impl FromExpr for EscapeMode {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        Self::from_lit(&LitStr::from_expr(expr)?)
+    }
+}
+
+impl Case {
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "lower" => Ok(Self::Lower),
+            "upper" => Ok(Self::Upper),
+            _ => Err(Error::new_spanned(lit, "Expected \"lower\" or \"upper\"")),
+        }
+    }
+}
+
+impl ParseOption for Case {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_lit(&LitStr::from_stream(input)?)
+    }
+}
+
+impl FromExpr for Case {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        Self::from_lit(&LitStr::from_expr(expr)?)
+    }
+}
+
+impl Align {
+    fn from_ident(ident: &Ident) -> syn::Result<Self> {
+        match ident.to_string().as_str() {
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "center" => Ok(Self::Center),
+            _ => Err(Error::new_spanned(
+                ident,
+                "Expected `left`, `right` or `center`",
+            )),
+        }
+    }
+}
+
+impl ParseOption for Align {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_ident(&Ident::from_stream(input)?)
+    }
+}
+
+impl FromExpr for Align {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        Self::from_ident(&Ident::from_expr(expr)?)
+    }
+}
+
+impl QuoteStyle {
+    fn from_ident(ident: &Ident) -> syn::Result<Self> {
+        match ident.to_string().as_str() {
+            "none" => Ok(Self::None),
+            "single" => Ok(Self::Single(ident.span())),
+            "double" => Ok(Self::Double),
+            _ => Err(Error::new_spanned(
+                ident,
+                "Expected `none`, `single` or `double`",
+            )),
+        }
+    }
+}
+
+impl ParseOption for QuoteStyle {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_ident(&Ident::from_stream(input)?)
+    }
+}
+
+impl FromExpr for QuoteStyle {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        Self::from_ident(&Ident::from_expr(expr)?)
+    }
+}
+
+impl ParseOption for Alternate {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        // A bare flag leaves the input at the next option, if any
+        if input.is_empty() || input.peek(Token![,]) {
+            Ok(Self::Flag)
+        } else {
+            Self::from_expr(Expr::from_stream(input)?)
+        }
+    }
+}
+
+impl FromExpr for Alternate {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        Ok(Self::Field(FieldMember::from_expr(expr)?.0))
+    }
+
+    fn boolean() -> Option<Self> {
+        Some(Self::Flag)
+    }
+}
+
+impl ParseOption for FieldPath {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_expr(Expr::from_stream(input)?)
+    }
+}
+
+impl FromExpr for FieldPath {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        let path = match expr {
+            Expr::Field(field) => {
+                let mut path = Self::from_expr(*field.base)?;
+                path.0.push(field.member);
+                return Ok(path);
+            }
+            // `0.1` is a float literal
+            Expr::Lit(ExprLit {
+                lit: Lit::Float(lit),
+                ..
+            }) => lit
+                .base10_digits()
+                .split('.')
+                .map(|idx| match idx.parse() {
+                    Ok(index) => Ok(Member::Unnamed(Index {
+                        index,
+                        span: lit.span(),
+                    })),
+                    Err(_) => Err(Error::new_spanned(&lit, "Expected a field path")),
+                })
+                .collect::<syn::Result<_>>()?,
+            expr => vec![FieldMember::from_expr(expr)?.0],
+        };
+
+        Ok(Self(path))
+    }
+}
+
+impl ParseOption for FieldMember {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_expr(Expr::from_stream(input)?)
+    }
+}
+
+impl FromExpr for FieldMember {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        let member = match expr {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(lit), ..
+            }) => Member::Unnamed(Index {
+                index: lit.base10_parse()?,
+                span: lit.span(),
+            }),
+            expr => Member::Named(Ident::from_expr(expr)?),
+        };
+
+        Ok(Self(member))
+    }
+}
+
+impl ParseOption for Redact {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Self::from_expr(input.parse()?)
+        } else {
+            RedactOptions::from_stream(input).map(Self)
+        }
+    }
+}
+
+impl FromExpr for Redact {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        Ok(Self(RedactOptions {
+            mask: Some(LitStr::from_expr(expr)?),
+            ..RedactOptions::default()
+        }))
+    }
+
+    fn boolean() -> Option<Self> {
+        Some(Self(RedactOptions::default()))
+    }
+}
+
+impl FmtTrait {
+    fn from_ident(ident: &Ident) -> syn::Result<Self> {
+        let name = ident.to_string();
+
+        if let Some(alias) = Alias::ALL.iter().find(move |a| a.trait_name == name) {
+            Ok(Self(alias.trait_name))
+        } else {
+            let names = Alias::ALL
+                .iter()
+                .map(move |a| a.trait_name)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            Err(Error::new_spanned(
+                ident,
+                format!("Expected one of the fmt traits: {names}"),
+            ))
+        }
+    }
+}
+
+impl ParseOption for FmtTrait {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_ident(&Ident::from_stream(input)?)
+    }
+}
+
+impl FromExpr for FmtTrait {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        Self::from_ident(&Ident::from_expr(expr)?)
+    }
+}
@@ -0,0 +1,313 @@
+use super::opts::Align;
+use super::runtime::Runtime;
+use super::FMT;
+use macroific::elements::module_prefix::OPTION;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Formatter settings to format the delegation target with instead of the caller's
+pub(crate) struct Overrides {
+    pub width: Option<usize>,
+    pub fill: Option<char>,
+    pub align: Option<Align>,
+    pub alternate: bool,
+    pub sign_plus: bool,
+    pub precision: Option<usize>,
+
+    /// Only apply the width & precision if the caller didn't specify their own
+    pub fallback: bool,
+}
+
+/// Positions of the format spec's parts, in order
+const FILL_ALIGN: usize = 0;
+const SIGN: usize = 1;
+const ALTERNATE: usize = 2;
+const WIDTH: usize = 3;
+const PRECISION: usize = 4;
+
+/// Parts of a format spec, indexed by the constants above
+type Parts<'a> = [&'a str; 5];
+
+impl Overrides {
+    pub fn is_empty(&self) -> bool {
+        self.width.is_none()
+            && self.fill.is_none()
+            && self.align.is_none()
+            && !self.alternate
+            && !self.sign_plus
+            && self.precision.is_none()
+    }
+
+    /// Wrap `call` so that it receives a formatter with the overrides applied. Anything that isn't
+    /// overridden, the caller's width, fill & alignment included, gets taken from the caller.
+    pub fn wrap(&self, runtime: &Runtime, call: &TokenStream) -> TokenStream {
+        let fmt_fn = runtime.fmt_fn();
+        let inner = quote!(#fmt_fn(|f: &mut #FMT::Formatter<'_>| #call));
+
+        if let (Some(width), false) = (self.width, self.fallback) {
+            let (fill_align, width) = self.padding(width);
+            return self.spec_call(
+                &inner,
+                Slot::Fixed(vec![(FILL_ALIGN, fill_align), (WIDTH, width)]),
+            );
+        }
+
+        if self.fill.is_some() || self.align.is_some() {
+            return self.fill_caller_width(runtime, &inner);
+        }
+
+        let left = quote!(#OPTION::Some(#FMT::Alignment::Left));
+        let right = quote!(#OPTION::Some(#FMT::Alignment::Right));
+        let center = quote!(#OPTION::Some(#FMT::Alignment::Center));
+
+        // The fill can't be passed as an argument so any fill other than a space gets applied
+        // to the rendered output instead
+        let pad = runtime.pad();
+        let unpadded = self.spec_call(&inner, Slot::Fixed(Vec::new()));
+        let padded = self.spec_call(&inner, self.caller_padding());
+
+        quote! {{
+            let __dd_width = f.width().unwrap_or(0);
+            let __dd_padding: u8 = match (f.width(), f.fill(), f.align()) {
+                (#OPTION::None, _, _) => 0,
+                _ if f.sign_aware_zero_pad() => 1,
+                (_, ' ', #OPTION::None) => 2,
+                (_, ' ', #left) => 3,
+                (_, ' ', #right) => 4,
+                (_, ' ', #center) => 5,
+                _ => 6,
+            };
+
+            if __dd_padding == 6 {
+                #pad(f, &#fmt_fn(|f: &mut #FMT::Formatter<'_>| #unpadded))
+            } else {
+                #padded
+            }
+        }}
+    }
+
+    /// Pad the output to the caller's width with the overridden fill & alignment, taking whichever
+    /// isn't overridden from the caller
+    fn fill_caller_width(&self, runtime: &Runtime, inner: &TokenStream) -> TokenStream {
+        let fmt_fn = runtime.fmt_fn();
+        let fill_as = runtime.fill_as();
+
+        let no_width = self.spec_call(inner, Slot::Fixed(self.fallback_padding()));
+        let zero_padded = self.spec_call(inner, Slot::fixed(WIDTH, "0__dd_width$"));
+        let unpadded = self.spec_call(inner, Slot::Fixed(Vec::new()));
+
+        // The `0` flag takes precedence over the alignment, same as in a format spec
+        let zero_pad = match self.fill {
+            Some('0') if self.align.is_none() => quote!(true),
+            Some(_) => quote!(false),
+            None => quote!(f.sign_aware_zero_pad()),
+        };
+        let fill = match self.fill {
+            Some(fill) => quote!(#fill),
+            None => quote!(f.fill()),
+        };
+        let align = match self.align {
+            Some(Align::Left) => quote!(#FMT::Alignment::Left),
+            Some(Align::Right) => quote!(#FMT::Alignment::Right),
+            Some(Align::Center) => quote!(#FMT::Alignment::Center),
+            None => quote!(f.align().unwrap_or(#FMT::Alignment::Right)),
+        };
+
+        quote! {{
+            let __dd_width = f.width().unwrap_or(0);
+            if f.width().is_none() {
+                #no_width
+            } else if #zero_pad {
+                #zero_padded
+            } else {
+                let flags = __dd_flags(f);
+                let (fill, align) = (#fill, #align);
+                let unpadded = #fmt_fn(|f: &mut #FMT::Formatter<'_>| #unpadded);
+                let mut counter = __DdCounter(0);
+                __dd_forward(&mut counter, flags, &unpadded)?;
+
+                #fill_as(f, counter.0, fill, align, &|f: &mut #FMT::Formatter<'_>| {
+                    __dd_forward(f, flags, &unpadded)
+                })
+            }
+        }}
+    }
+
+    /// The overridden width's padding if the caller has no width of their own
+    fn fallback_padding(&self) -> Vec<(usize, String)> {
+        match self.width {
+            Some(width) => {
+                let (fill_align, width) = self.padding(width);
+                vec![(FILL_ALIGN, fill_align), (WIDTH, width)]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Padding taken from the caller's formatter based on the `__dd_padding` computed in
+    /// [`wrap`](Self::wrap), falling back to the overridden width if the caller has none
+    fn caller_padding(&self) -> Slot {
+        let none = self.fallback_padding();
+
+        let width = move |fill_align: &str, width: &str| {
+            vec![(FILL_ALIGN, fill_align.into()), (WIDTH, width.into())]
+        };
+
+        Slot::Runtime {
+            scrutinee: quote!(__dd_padding),
+            arms: vec![
+                (quote!(0), none),
+                (quote!(1), width("", "0__dd_width$")),
+                (quote!(2), width("", "__dd_width$")),
+                (quote!(3), width("<", "__dd_width$")),
+                (quote!(4), width(">", "__dd_width$")),
+                (quote!(_), width("^", "__dd_width$")),
+            ],
+        }
+    }
+
+    /// The fill & alignment and the zero flag & width parts of the format spec
+    fn padding(&self, width: usize) -> (String, String) {
+        let align = self.align.map(move |align| match align {
+            Align::Left => '<',
+            Align::Right => '>',
+            Align::Center => '^',
+        });
+
+        match (self.fill, align) {
+            // `{:010}` is the sign-aware zero padding flag
+            (Some('0'), None) => (String::new(), format!("0{width}")),
+            (Some(fill), align) => (format!("{fill}{}", align.unwrap_or('>')), width.to_string()),
+            (None, Some(align)) => (align.to_string(), width.to_string()),
+            (None, None) => (String::new(), width.to_string()),
+        }
+    }
+
+    /// Write `inner` with a format spec built from the overrides & the given padding, branching
+    /// on the caller's flags for anything that isn't overridden
+    fn spec_call(&self, inner: &TokenStream, padding: Slot) -> TokenStream {
+        let mut prefix = TokenStream::new();
+        let slots = [
+            padding,
+            if self.sign_plus {
+                Slot::fixed(SIGN, "+")
+            } else {
+                Slot::flag(quote!(f.sign_plus()), SIGN, "+")
+            },
+            if self.alternate {
+                Slot::fixed(ALTERNATE, "#")
+            } else {
+                Slot::flag(quote!(f.alternate()), ALTERNATE, "#")
+            },
+            match (self.precision, self.fallback) {
+                (Some(precision), false) => Slot::fixed(PRECISION, &format!(".{precision}")),
+                (Some(precision), true) => {
+                    prefix = quote!(let __dd_precision = f.precision().unwrap_or(#precision););
+                    Slot::fixed(PRECISION, ".__dd_precision$")
+                }
+                (None, _) => Slot::Runtime {
+                    scrutinee: quote!(f.precision()),
+                    arms: vec![
+                        (quote!(#OPTION::None), Vec::new()),
+                        (
+                            quote!(#OPTION::Some(__dd_precision)),
+                            vec![(PRECISION, ".__dd_precision$".into())],
+                        ),
+                    ],
+                },
+            },
+        ];
+
+        let scrutinees = slots.iter().filter_map(move |slot| match slot {
+            Slot::Fixed(_) => None,
+            Slot::Runtime { scrutinee, .. } => Some(scrutinee),
+        });
+
+        let arms = combinations(&slots)
+            .into_iter()
+            .map(move |(patterns, parts)| {
+                let [fill_align, sign, alternate, width, precision] = parts;
+                let spec = format!("{{:{fill_align}{sign}{alternate}{width}{precision}}}");
+
+                let mut args = TokenStream::new();
+                if width.contains('$') {
+                    args.extend(quote!(, __dd_width = __dd_width));
+                }
+                if precision.contains('$') {
+                    args.extend(quote!(, __dd_precision = __dd_precision));
+                }
+
+                quote! {
+                    (#(#patterns,)*) => f.write_fmt(::core::format_args!(#spec, __dd_inner #args)),
+                }
+            });
+
+        quote! {{
+            #prefix
+            let __dd_inner = #inner;
+            match (#(#scrutinees,)*) {
+                #(#arms)*
+            }
+        }}
+    }
+}
+
+/// A part of the format spec that's either overridden or taken from the caller's formatter. Each
+/// option writes to one or more of the spec's [`Parts`].
+enum Slot {
+    Fixed(Vec<(usize, String)>),
+    Runtime {
+        scrutinee: TokenStream,
+        arms: Vec<(TokenStream, Vec<(usize, String)>)>,
+    },
+}
+
+impl Slot {
+    fn fixed(part: usize, spec: &str) -> Self {
+        Self::Fixed(vec![(part, spec.into())])
+    }
+
+    fn flag(scrutinee: TokenStream, part: usize, spec: &str) -> Self {
+        Self::Runtime {
+            scrutinee,
+            arms: vec![
+                (quote!(false), Vec::new()),
+                (quote!(true), vec![(part, spec.into())]),
+            ],
+        }
+    }
+}
+
+/// Every combination of the runtime slots' arms: the patterns to match along with the resulting
+/// spec parts
+fn combinations(slots: &[Slot]) -> Vec<(Vec<&TokenStream>, Parts<'_>)> {
+    fn apply<'a>(mut parts: Parts<'a>, specs: &'a [(usize, String)]) -> Parts<'a> {
+        for (part, spec) in specs {
+            parts[*part] = spec;
+        }
+        parts
+    }
+
+    let mut out = vec![(Vec::new(), [""; 5])];
+
+    for slot in slots {
+        out = match slot {
+            Slot::Fixed(specs) => out
+                .into_iter()
+                .map(move |(patterns, parts)| (patterns, apply(parts, specs)))
+                .collect(),
+            Slot::Runtime { arms, .. } => out
+                .iter()
+                .flat_map(move |(patterns, parts)| {
+                    arms.iter().map(move |(pattern, specs)| {
+                        let mut patterns = patterns.clone();
+                        patterns.push(pattern);
+                        (patterns, apply(*parts, specs))
+                    })
+                })
+                .collect(),
+        };
+    }
+
+    out
+}
@@ -0,0 +1,107 @@
+use macroific::prelude::*;
+use syn::parse::ParseStream;
+use syn::{Error, Expr, LitStr};
+
+/// Casing to apply to struct & variant identifiers when they get output as strings.
+#[derive(Copy, Clone)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+const RULE_NAMES: &str = "\"lowercase\", \"UPPERCASE\", \"PascalCase\", \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \"kebab-case\", \"SCREAMING-KEBAB-CASE\"";
+
+impl RenameRule {
+    pub fn apply(self, ident: &str) -> String {
+        let words = split_words(ident);
+
+        match self {
+            Self::Lower => words.concat().to_lowercase(),
+            Self::Upper => words.concat().to_uppercase(),
+            Self::Pascal => words.iter().map(|w| capitalise(w)).collect(),
+            Self::Camel => {
+                let mut words = words.iter();
+                let mut out = words.next().map(|w| w.to_lowercase()).unwrap_or_default();
+                out.extend(words.map(|w| capitalise(w)));
+                out
+            }
+            Self::Snake => words.join("_").to_lowercase(),
+            Self::ScreamingSnake => words.join("_").to_uppercase(),
+            Self::Kebab => words.join("-").to_lowercase(),
+            Self::ScreamingKebab => words.join("-").to_uppercase(),
+        }
+    }
+
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => {
+                let msg = format!("Unknown casing. Expected one of {RULE_NAMES}");
+                return Err(Error::new_spanned(lit, msg));
+            }
+        })
+    }
+}
+
+/// Split an identifier into words on underscores, lowercase → uppercase transitions and the
+/// ends of acronyms, e.g. `HTTPStatus_code` → `HTTP`, `Status`, `code`.
+fn split_words(ident: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+
+    for segment in ident.split('_').filter(|s| !s.is_empty()) {
+        let chars: Vec<(usize, char)> = segment.char_indices().collect();
+        let mut start = 0;
+
+        for (pos, &(idx, ch)) in chars.iter().enumerate().skip(1) {
+            let prev = chars[pos - 1].1;
+            let next_is_lower = chars.get(pos + 1).is_some_and(|&(_, c)| c.is_lowercase());
+
+            let is_boundary = ch.is_uppercase() && (!prev.is_uppercase() || next_is_lower);
+
+            if is_boundary {
+                out.push(&segment[start..idx]);
+                start = idx;
+            }
+        }
+
+        out.push(&segment[start..]);
+    }
+
+    out
+}
+
+fn capitalise(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+impl ParseOption for RenameRule {
+    fn from_stream(input: ParseStream) -> syn::Result<Self> {
+        Self::from_lit(&LitStr::from_stream(input)?)
+    }
+}
+
+impl FromExpr for RenameRule {
+    fn from_expr(expr: Expr) -> syn::Result<Self> {
+        Self::from_lit(&LitStr::from_expr(expr)?)
+    }
+}
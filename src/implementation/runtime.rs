@@ -0,0 +1,652 @@
+use super::opts::EscapeMode;
+use super::FMT;
+use macroific::elements::module_prefix::{OPTION, RESULT};
+use macroific::prelude::*;
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+use std::cell::Cell;
+
+/// Size of the stack buffer `pad` mode renders into
+const PAD_BUF_SIZE: usize = 256;
+
+/// Helper items emitted into the generated `fmt` function. Each helper is only emitted if it was
+/// requested while rendering the function body.
+#[derive(Default)]
+pub(crate) struct Runtime {
+    fmt_fn: Cell<bool>,
+    counter: Cell<bool>,
+    forward: Cell<bool>,
+    fill: Cell<bool>,
+    decorate: Cell<bool>,
+    pad: Cell<bool>,
+    measure: Cell<bool>,
+    limit: Cell<bool>,
+    truncate: Cell<bool>,
+    escape: Cell<bool>,
+    escape_html: Cell<bool>,
+    escape_json: Cell<bool>,
+    escape_shell: Cell<bool>,
+    escape_debug: Cell<bool>,
+    case: Cell<bool>,
+}
+
+impl Runtime {
+    /// `__DdFmtFn(|f| ...)` implements `Display` & `Debug` through the given closure
+    pub fn fmt_fn(&self) -> Ident {
+        self.fmt_fn.set(true);
+        Ident::create("__DdFmtFn")
+    }
+
+    /// `__dd_decorate(f, prefix, suffix, &inner)` writes `inner` between `prefix` & `suffix`,
+    /// padding the output as a whole if the formatter has a width set
+    pub fn decorate(&self) -> Ident {
+        self.decorate.set(true);
+        self.require_fill();
+        Ident::create("__dd_decorate")
+    }
+
+    /// `__dd_pad(f, &inner)` renders `inner` into a buffer & pads it if the formatter has a width set
+    pub fn pad(&self) -> Ident {
+        self.pad.set(true);
+        self.require_fill();
+        Ident::create("__dd_pad")
+    }
+
+    /// `__dd_measure(f, &write)` runs `write` on the formatter, padding its output as a whole if
+    /// the formatter has a width set by running it once more to count the characters
+    pub fn measure(&self) -> Ident {
+        self.measure.set(true);
+        self.require_fill();
+        Ident::create("__dd_measure")
+    }
+
+    /// `__dd_limit(out, flags, limit, &inner)` writes at most `limit` characters of `inner` with
+    /// the given flags, returning whether the output got cut short
+    pub fn limit(&self) -> Ident {
+        self.limit.set(true);
+        self.forward.set(true);
+        Ident::create("__dd_limit")
+    }
+
+    /// `__dd_truncate(f, limit, ellipsis, &inner)` writes at most `limit` characters of `inner`
+    /// followed by `ellipsis` if it got cut short, padding the output as a whole if the formatter
+    /// has a width set
+    pub fn truncate(&self) -> Ident {
+        self.truncate.set(true);
+        self.limit();
+        self.require_fill();
+        Ident::create("__dd_truncate")
+    }
+
+    /// `__DdEscape { out, escape, quote }` escapes everything written to it through the
+    /// returned function, which must be passed as `escape`
+    pub fn escape(&self, mode: EscapeMode) -> (Ident, Ident) {
+        self.escape.set(true);
+        self.forward.set(true);
+
+        let (flag, name) = match mode {
+            EscapeMode::Html => (&self.escape_html, "__dd_escape_html"),
+            EscapeMode::Json => (&self.escape_json, "__dd_escape_json"),
+            EscapeMode::Shell => (&self.escape_shell, "__dd_escape_shell"),
+            EscapeMode::Debug => (&self.escape_debug, "__dd_escape_debug"),
+        };
+        flag.set(true);
+
+        (Ident::create("__DdEscape"), Ident::create(name))
+    }
+
+    /// `__dd_case(out, flags, upper, &inner)` writes `inner` converted to upper or lower case
+    pub fn case(&self) -> Ident {
+        self.case.set(true);
+        self.forward.set(true);
+        Ident::create("__dd_case")
+    }
+
+    /// `__dd_fill_as(f, len, fill, align, &body)` writes `body`, which outputs `len` characters,
+    /// padded to the formatter's width with the given fill & alignment
+    pub fn fill_as(&self) -> Ident {
+        self.require_fill();
+        Ident::create("__dd_fill_as")
+    }
+
+    fn require_fill(&self) {
+        self.fill.set(true);
+        self.counter.set(true);
+        self.forward.set(true);
+    }
+}
+
+impl ToTokens for Runtime {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let helpers = [
+            (&self.fmt_fn, fmt_fn_tokens as fn() -> TokenStream),
+            (&self.counter, counter_tokens),
+            (&self.forward, forward_tokens),
+            (&self.fill, fill_tokens),
+            (&self.decorate, decorate_tokens),
+            (&self.pad, pad_tokens),
+            (&self.measure, measure_tokens),
+            (&self.limit, limit_tokens),
+            (&self.truncate, truncate_tokens),
+            (&self.escape, escape_tokens),
+            (&self.escape_html, escape_html_tokens),
+            (&self.escape_json, escape_json_tokens),
+            (&self.escape_shell, escape_shell_tokens),
+            (&self.escape_debug, escape_debug_tokens),
+            (&self.case, case_tokens),
+        ];
+
+        for (requested, render) in helpers {
+            if requested.get() {
+                tokens.extend(render());
+            }
+        }
+    }
+}
+
+fn fmt_fn_tokens() -> TokenStream {
+    quote! {
+        struct __DdFmtFn<F>(F);
+        impl<F: Fn(&mut #FMT::Formatter<'_>) -> #FMT::Result> #FMT::Display for __DdFmtFn<F> {
+            #[inline]
+            fn fmt(&self, f: &mut #FMT::Formatter<'_>) -> #FMT::Result {
+                (self.0)(f)
+            }
+        }
+        impl<F: Fn(&mut #FMT::Formatter<'_>) -> #FMT::Result> #FMT::Debug for __DdFmtFn<F> {
+            #[inline]
+            fn fmt(&self, f: &mut #FMT::Formatter<'_>) -> #FMT::Result {
+                (self.0)(f)
+            }
+        }
+    }
+}
+
+fn counter_tokens() -> TokenStream {
+    quote! {
+        /// Counts the characters written to it
+        struct __DdCounter(usize);
+        impl #FMT::Write for __DdCounter {
+            #[inline]
+            fn write_str(&mut self, s: &str) -> #FMT::Result {
+                self.0 += s.chars().count();
+                #RESULT::Ok(())
+            }
+        }
+    }
+}
+
+fn forward_tokens() -> TokenStream {
+    quote! {
+        /// The `(alternate, sign_plus, precision)` flags, i.e. the ones that don't concern padding
+        #[inline]
+        fn __dd_flags(f: &#FMT::Formatter<'_>) -> (bool, bool, #OPTION<usize>) {
+            (f.alternate(), f.sign_plus(), f.precision())
+        }
+
+        /// Write `inner` to `out` with the given [`__dd_flags`]
+        fn __dd_forward(
+            out: &mut dyn #FMT::Write,
+            flags: (bool, bool, #OPTION<usize>),
+            inner: &dyn #FMT::Display,
+        ) -> #FMT::Result {
+            match flags {
+                (false, false, #OPTION::None) => ::core::write!(out, "{}", inner),
+                (true, false, #OPTION::None) => ::core::write!(out, "{:#}", inner),
+                (false, true, #OPTION::None) => ::core::write!(out, "{:+}", inner),
+                (true, true, #OPTION::None) => ::core::write!(out, "{:+#}", inner),
+                (false, false, #OPTION::Some(p)) => ::core::write!(out, "{:.*}", p, inner),
+                (true, false, #OPTION::Some(p)) => ::core::write!(out, "{:#.*}", p, inner),
+                (false, true, #OPTION::Some(p)) => ::core::write!(out, "{:+.*}", p, inner),
+                (true, true, #OPTION::Some(p)) => ::core::write!(out, "{:+#.*}", p, inner),
+            }
+        }
+    }
+}
+
+fn fill_tokens() -> TokenStream {
+    quote! {
+        /// Write `body`, which outputs `len` characters, surrounded by the formatter's fill
+        /// according to its width & alignment
+        fn __dd_fill(
+            f: &mut #FMT::Formatter<'_>,
+            len: usize,
+            body: &dyn Fn(&mut #FMT::Formatter<'_>) -> #FMT::Result,
+        ) -> #FMT::Result {
+            let fill = f.fill();
+            let align = f.align().unwrap_or(#FMT::Alignment::Left);
+            __dd_fill_as(f, len, fill, align, body)
+        }
+
+        /// [`__dd_fill`] with the given fill & alignment instead of the formatter's
+        fn __dd_fill_as(
+            f: &mut #FMT::Formatter<'_>,
+            len: usize,
+            fill: char,
+            align: #FMT::Alignment,
+            body: &dyn Fn(&mut #FMT::Formatter<'_>) -> #FMT::Result,
+        ) -> #FMT::Result {
+            let padding = f.width().unwrap_or(0).saturating_sub(len);
+            let (pre, post) = match align {
+                #FMT::Alignment::Right => (padding, 0),
+                #FMT::Alignment::Center => (padding / 2, padding - padding / 2),
+                #FMT::Alignment::Left => (0, padding),
+            };
+
+            for _ in 0..pre {
+                #FMT::Write::write_char(f, fill)?;
+            }
+
+            body(f)?;
+
+            for _ in 0..post {
+                #FMT::Write::write_char(f, fill)?;
+            }
+
+            #RESULT::Ok(())
+        }
+    }
+}
+
+fn decorate_tokens() -> TokenStream {
+    quote! {
+        fn __dd_decorate(
+            f: &mut #FMT::Formatter<'_>,
+            prefix: &str,
+            suffix: &str,
+            inner: &dyn #FMT::Display,
+        ) -> #FMT::Result {
+            if f.width().is_none() {
+                f.write_str(prefix)?;
+                #FMT::Display::fmt(inner, f)?;
+                return f.write_str(suffix);
+            }
+
+            let flags = __dd_flags(f);
+            let mut counter = __DdCounter(prefix.chars().count() + suffix.chars().count());
+            __dd_forward(&mut counter, flags, inner)?;
+
+            __dd_fill(f, counter.0, &|f: &mut #FMT::Formatter<'_>| {
+                f.write_str(prefix)?;
+                __dd_forward(f, flags, inner)?;
+                f.write_str(suffix)
+            })
+        }
+    }
+}
+
+fn measure_tokens() -> TokenStream {
+    quote! {
+        fn __dd_measure(
+            f: &mut #FMT::Formatter<'_>,
+            write: &dyn Fn(&mut dyn #FMT::Write) -> #FMT::Result,
+        ) -> #FMT::Result {
+            if f.width().is_none() {
+                return write(f);
+            }
+
+            let mut counter = __DdCounter(0);
+            write(&mut counter)?;
+
+            __dd_fill(f, counter.0, &|f: &mut #FMT::Formatter<'_>| write(f))
+        }
+    }
+}
+
+fn limit_tokens() -> TokenStream {
+    quote! {
+        /// Writes up to `remaining` characters, then fails
+        struct __DdLimit<'a> {
+            out: &'a mut dyn #FMT::Write,
+            remaining: usize,
+            truncated: bool,
+        }
+        impl #FMT::Write for __DdLimit<'_> {
+            fn write_str(&mut self, s: &str) -> #FMT::Result {
+                if self.truncated {
+                    return #RESULT::Err(#FMT::Error);
+                }
+
+                match s.char_indices().nth(self.remaining) {
+                    #OPTION::Some((idx, _)) => {
+                        self.out.write_str(&s[..idx])?;
+                        self.remaining = 0;
+                        self.truncated = true;
+                        #RESULT::Err(#FMT::Error)
+                    }
+                    #OPTION::None => {
+                        self.remaining -= s.chars().count();
+                        self.out.write_str(s)
+                    }
+                }
+            }
+        }
+
+        fn __dd_limit(
+            out: &mut dyn #FMT::Write,
+            flags: (bool, bool, #OPTION<usize>),
+            limit: usize,
+            inner: &dyn #FMT::Display,
+        ) -> #RESULT<bool, #FMT::Error> {
+            let mut writer = __DdLimit {
+                out,
+                remaining: limit,
+                truncated: false,
+            };
+
+            match __dd_forward(&mut writer, flags, inner) {
+                #RESULT::Ok(()) => #RESULT::Ok(false),
+                #RESULT::Err(_) if writer.truncated => #RESULT::Ok(true),
+                #RESULT::Err(e) => #RESULT::Err(e),
+            }
+        }
+    }
+}
+
+fn truncate_tokens() -> TokenStream {
+    quote! {
+        fn __dd_truncate(
+            f: &mut #FMT::Formatter<'_>,
+            limit: usize,
+            ellipsis: &str,
+            inner: &dyn #FMT::Display,
+        ) -> #FMT::Result {
+            let flags = __dd_flags(f);
+            let write = |out: &mut dyn #FMT::Write| -> #FMT::Result {
+                if __dd_limit(out, flags, limit, inner)? {
+                    out.write_str(ellipsis)
+                } else {
+                    #RESULT::Ok(())
+                }
+            };
+
+            if f.width().is_none() {
+                return write(f);
+            }
+
+            let mut counter = __DdCounter(0);
+            write(&mut counter)?;
+
+            __dd_fill(f, counter.0, &|f: &mut #FMT::Formatter<'_>| write(f))
+        }
+    }
+}
+
+fn pad_tokens() -> TokenStream {
+    // Without an allocator, output that doesn't fit in the buffer gets rendered twice: once to
+    // measure it & once to write it
+    let overflow = if cfg!(feature = "alloc") {
+        quote! {
+            extern crate alloc;
+
+            let mut buf = alloc::string::String::new();
+            __dd_forward(&mut buf, flags, inner)?;
+            __dd_pad_str(f, &buf, flags)
+        }
+    } else {
+        quote! {
+            let mut counter = __DdCounter(0);
+            __dd_forward(&mut counter, flags, inner)?;
+            __dd_fill(f, counter.0, &|f: &mut #FMT::Formatter<'_>| __dd_forward(f, flags, inner))
+        }
+    };
+
+    quote! {
+        /// Fixed-capacity buffer that errors once it runs out of space
+        struct __DdBuf {
+            buf: [u8; #PAD_BUF_SIZE],
+            len: usize,
+            overflowed: bool,
+        }
+        impl #FMT::Write for __DdBuf {
+            fn write_str(&mut self, s: &str) -> #FMT::Result {
+                let end = self.len + s.len();
+                if end > self.buf.len() {
+                    self.overflowed = true;
+                    return #RESULT::Err(#FMT::Error);
+                }
+
+                self.buf[self.len..end].copy_from_slice(s.as_bytes());
+                self.len = end;
+                #RESULT::Ok(())
+            }
+        }
+
+        /// Pad a pre-rendered string. The precision was already used when rendering it so it mustn't
+        /// truncate the string.
+        fn __dd_pad_str(
+            f: &mut #FMT::Formatter<'_>,
+            s: &str,
+            flags: (bool, bool, #OPTION<usize>),
+        ) -> #FMT::Result {
+            if flags.2.is_none() {
+                f.pad(s)
+            } else {
+                __dd_fill(f, s.chars().count(), &|f: &mut #FMT::Formatter<'_>| f.write_str(s))
+            }
+        }
+
+        fn __dd_pad(f: &mut #FMT::Formatter<'_>, inner: &dyn #FMT::Display) -> #FMT::Result {
+            if f.width().is_none() {
+                return #FMT::Display::fmt(inner, f);
+            }
+
+            let flags = __dd_flags(f);
+            let mut buf = __DdBuf {
+                buf: [0; #PAD_BUF_SIZE],
+                len: 0,
+                overflowed: false,
+            };
+
+            match __dd_forward(&mut buf, flags, inner) {
+                #RESULT::Ok(()) => {
+                    let s = ::core::str::from_utf8(&buf.buf[..buf.len]).map_err(|_| #FMT::Error)?;
+                    return __dd_pad_str(f, s, flags);
+                }
+                #RESULT::Err(e) if !buf.overflowed => return #RESULT::Err(e),
+                #RESULT::Err(_) => {}
+            }
+
+            #overflow
+        }
+    }
+}
+
+fn escape_tokens() -> TokenStream {
+    quote! {
+        /// Writes characters through the `escape` function, which returns `false` for characters
+        /// that don't need escaping without writing anything
+        struct __DdEscape<'a> {
+            out: &'a mut dyn #FMT::Write,
+            escape: fn(&mut dyn #FMT::Write, char, #OPTION<char>) -> #RESULT<bool, #FMT::Error>,
+            quote: #OPTION<char>,
+        }
+        impl #FMT::Write for __DdEscape<'_> {
+            fn write_str(&mut self, s: &str) -> #FMT::Result {
+                for ch in s.chars() {
+                    if !(self.escape)(self.out, ch, self.quote)? {
+                        self.out.write_char(ch)?;
+                    }
+                }
+
+                #RESULT::Ok(())
+            }
+        }
+    }
+}
+
+fn escape_html_tokens() -> TokenStream {
+    quote! {
+        fn __dd_escape_html(
+            out: &mut dyn #FMT::Write,
+            ch: char,
+            _: #OPTION<char>,
+        ) -> #RESULT<bool, #FMT::Error> {
+            let entity = match ch {
+                '&' => "&amp;",
+                '<' => "&lt;",
+                '>' => "&gt;",
+                '"' => "&quot;",
+                '\'' => "&#39;",
+                _ => return #RESULT::Ok(false),
+            };
+
+            out.write_str(entity)?;
+            #RESULT::Ok(true)
+        }
+    }
+}
+
+fn escape_json_tokens() -> TokenStream {
+    quote! {
+        fn __dd_escape_json(
+            out: &mut dyn #FMT::Write,
+            ch: char,
+            _: #OPTION<char>,
+        ) -> #RESULT<bool, #FMT::Error> {
+            let escaped = match ch {
+                '"' => "\\\"",
+                '\\' => "\\\\",
+                '\n' => "\\n",
+                '\r' => "\\r",
+                '\t' => "\\t",
+                '\u{8}' => "\\b",
+                '\u{c}' => "\\f",
+                ch if (ch as u32) < 0x20 => {
+                    ::core::write!(out, "\\u{:04x}", ch as u32)?;
+                    return #RESULT::Ok(true);
+                }
+                _ => return #RESULT::Ok(false),
+            };
+
+            out.write_str(escaped)?;
+            #RESULT::Ok(true)
+        }
+    }
+}
+
+fn escape_shell_tokens() -> TokenStream {
+    quote! {
+        fn __dd_escape_shell(
+            out: &mut dyn #FMT::Write,
+            ch: char,
+            quote: #OPTION<char>,
+        ) -> #RESULT<bool, #FMT::Error> {
+            match quote {
+                // Nothing can be escaped within single quotes; close them, write an escaped
+                // quote & reopen them
+                #OPTION::Some('\'') if ch == '\'' => out.write_str("'\\''")?,
+                #OPTION::Some('\'') => return #RESULT::Ok(false),
+                #OPTION::Some(_) if ::core::matches!(ch, '"' | '\\' | '$' | '`') => {
+                    out.write_char('\\')?;
+                    out.write_char(ch)?;
+                }
+                #OPTION::Some(_) => return #RESULT::Ok(false),
+                #OPTION::None if ch == '\n' => out.write_str("'\n'")?,
+                #OPTION::None if ch.is_ascii_alphanumeric()
+                    || !ch.is_ascii()
+                    || ::core::matches!(ch, '_' | '@' | '%' | '+' | '=' | ':' | ',' | '.' | '/' | '-') =>
+                {
+                    return #RESULT::Ok(false);
+                }
+                #OPTION::None => {
+                    out.write_char('\\')?;
+                    out.write_char(ch)?;
+                }
+            }
+
+            #RESULT::Ok(true)
+        }
+    }
+}
+
+fn escape_debug_tokens() -> TokenStream {
+    quote! {
+        fn __dd_escape_debug(
+            out: &mut dyn #FMT::Write,
+            ch: char,
+            quote: #OPTION<char>,
+        ) -> #RESULT<bool, #FMT::Error> {
+            // Only the quote in use needs escaping
+            if (ch == '\'' || ch == '"') && quote != #OPTION::Some(ch) {
+                return #RESULT::Ok(false);
+            }
+
+            let escaped = ch.escape_debug();
+            if ::core::iter::ExactSizeIterator::len(&escaped) == 1 {
+                return #RESULT::Ok(false);
+            }
+
+            ::core::write!(out, "{}", escaped)?;
+            #RESULT::Ok(true)
+        }
+    }
+}
+
+fn case_tokens() -> TokenStream {
+    quote! {
+        /// Converts everything written to it to upper or lower case
+        struct __DdCase<'a> {
+            out: &'a mut dyn #FMT::Write,
+            upper: bool,
+
+            /// Whether the previous character was a letter
+            after_letter: bool,
+
+            /// A lowercased `Σ` is `ς` at the end of a word & `σ` anywhere else, which can only be
+            /// determined by the following character
+            pending_sigma: bool,
+        }
+        impl __DdCase<'_> {
+            fn flush_sigma(&mut self, end_of_word: bool) -> #FMT::Result {
+                if self.pending_sigma {
+                    self.pending_sigma = false;
+                    self.out.write_char(if end_of_word { '\u{3c2}' } else { '\u{3c3}' })?;
+                }
+
+                #RESULT::Ok(())
+            }
+        }
+        impl #FMT::Write for __DdCase<'_> {
+            fn write_str(&mut self, s: &str) -> #FMT::Result {
+                for ch in s.chars() {
+                    let is_letter = ch.is_alphabetic();
+                    self.flush_sigma(!is_letter)?;
+
+                    if self.upper {
+                        for upper in ch.to_uppercase() {
+                            self.out.write_char(upper)?;
+                        }
+                    } else if ch == '\u{3a3}' && self.after_letter {
+                        self.pending_sigma = true;
+                    } else {
+                        for lower in ch.to_lowercase() {
+                            self.out.write_char(lower)?;
+                        }
+                    }
+
+                    self.after_letter = is_letter;
+                }
+
+                #RESULT::Ok(())
+            }
+        }
+
+        fn __dd_case(
+            out: &mut dyn #FMT::Write,
+            flags: (bool, bool, #OPTION<usize>),
+            upper: bool,
+            inner: &dyn #FMT::Display,
+        ) -> #FMT::Result {
+            let mut writer = __DdCase {
+                out,
+                upper,
+                after_letter: false,
+                pending_sigma: false,
+            };
+
+            __dd_forward(&mut writer, flags, inner)?;
+            writer.flush_sigma(true)
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use std::ops::Range;
+use syn::ext::IdentExt;
+use syn::{Error, Fields, Index, LitStr, Member, Type};
+
+/// A `fmt = "..."` template, validated against & rewritten to only reference named arguments
+pub(crate) struct Template {
+    fmt: LitStr,
+    args: Vec<TemplateArg>,
+}
+
+/// A field referenced by a [`Template`]
+struct TemplateArg {
+    binding: Ident,
+    member: Member,
+    ty: Type,
+
+    /// Used as a width/precision parameter, which `format_args!` only accepts as a `usize` value
+    by_value: bool,
+
+    /// [`fmt`](::core::fmt) traits the field gets formatted with
+    traits: Vec<&'static str>,
+}
+
+struct FieldRef<'a> {
+    ident: Option<&'a Ident>,
+    ty: &'a Type,
+}
+
+struct Parser<'a> {
+    lit: &'a LitStr,
+    src: &'a str,
+    fields: Vec<FieldRef<'a>>,
+    args: Vec<TemplateArg>,
+    next_positional: usize,
+    out: String,
+}
+
+impl Template {
+    pub fn parse(lit: &LitStr, fields: &Fields) -> syn::Result<Self> {
+        let src = lit.value();
+        let fields = fields
+            .iter()
+            .map(move |f| FieldRef {
+                ident: f.ident.as_ref(),
+                ty: &f.ty,
+            })
+            .collect();
+
+        let mut parser = Parser {
+            lit,
+            src: &src,
+            fields,
+            args: Vec::new(),
+            next_positional: 0,
+            out: String::with_capacity(src.len()),
+        };
+        parser.run()?;
+
+        Ok(Self {
+            fmt: LitStr::new(&parser.out, lit.span()),
+            args: parser.args,
+        })
+    }
+
+    /// `member: binding` pairs for destructuring the fields used by the template
+    pub fn pattern(&self) -> TokenStream {
+        let members = self.args.iter().map(move |a| &a.member);
+        let bindings = self.args.iter().map(move |a| &a.binding);
+
+        quote!(#(#members: #bindings,)*)
+    }
+
+    /// Render a `write!` call. Fields are read from `self` if `from_self` is `true` or from
+    /// the bindings of [`pattern`](Self::pattern) otherwise.
+    pub fn render(&self, from_self: bool) -> TokenStream {
+        let fmt = &self.fmt;
+        let args = self.args.iter().map(move |arg| {
+            let TemplateArg {
+                binding,
+                member,
+                by_value,
+                ..
+            } = arg;
+
+            let value = match (from_self, *by_value) {
+                (true, true) => quote!(self.#member),
+                (true, false) => quote!(&self.#member),
+                (false, true) => quote!(*#binding),
+                (false, false) => binding.to_token_stream(),
+            };
+
+            quote!(#binding = #value)
+        });
+
+        quote!(f.write_fmt(::core::format_args!(#fmt, #(#args),*)))
+    }
+
+    /// Field types along with the [`fmt`](::core::fmt) traits they need to implement
+    pub fn predicates(&self) -> impl Iterator<Item = (&Type, &'static str)> {
+        self.args
+            .iter()
+            .flat_map(move |arg| arg.traits.iter().map(move |t| (&arg.ty, *t)))
+    }
+}
+
+impl Parser<'_> {
+    fn run(&mut self) -> syn::Result<()> {
+        let src = self.src;
+        let mut chars = src.char_indices().peekable();
+
+        while let Some((idx, ch)) = chars.next() {
+            match ch {
+                '{' if matches!(chars.peek(), Some((_, '{'))) => {
+                    chars.next();
+                    self.out.push_str("{{");
+                }
+                '}' if matches!(chars.peek(), Some((_, '}'))) => {
+                    chars.next();
+                    self.out.push_str("}}");
+                }
+                '{' => {
+                    let Some(end) = src[idx..].find('}').map(move |i| idx + i) else {
+                        return Err(self.error(idx..src.len(), "Unterminated placeholder"));
+                    };
+
+                    self.placeholder(idx..end + 1)?;
+                    while chars.next_if(move |&(i, _)| i <= end).is_some() {}
+                }
+                '}' => {
+                    return Err(self.error(idx..idx + 1, "Unmatched `}`; use `}}` to escape it"));
+                }
+                ch => self.out.push(ch),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle a `{...}` placeholder spanning `range`, braces included
+    fn placeholder(&mut self, range: Range<usize>) -> syn::Result<()> {
+        let inner = &self.src[range.start + 1..range.end - 1];
+        let (arg, spec) = match inner.find(':') {
+            Some(colon) => (inner[..colon].trim(), Some(&inner[colon + 1..])),
+            None => (inner.trim(), None),
+        };
+
+        let field_idx = if arg.is_empty() {
+            let idx = self.next_positional;
+            self.next_positional += 1;
+            self.resolve(&idx.to_string(), &range)?
+        } else {
+            self.resolve(arg, &range)?
+        };
+
+        let trait_name = trait_for_spec(spec.unwrap_or_default());
+        let binding = self.use_field(field_idx, Some(trait_name), false);
+
+        self.out.push('{');
+        self.out.push_str(&binding.to_string());
+
+        if let Some(spec) = spec {
+            self.out.push(':');
+            self.spec(spec, &range)?;
+        }
+
+        self.out.push('}');
+
+        Ok(())
+    }
+
+    /// Copy the format spec over, replacing `name$` & `0$` parameters with their bindings
+    fn spec(&mut self, spec: &str, range: &Range<usize>) -> syn::Result<()> {
+        if spec.contains(".*") {
+            return Err(self.error(range.clone(), "`.*` precision is not supported"));
+        }
+
+        let mut word = String::new();
+
+        for ch in spec.chars() {
+            if ch.is_alphanumeric() || ch == '_' {
+                word.push(ch);
+                continue;
+            }
+
+            if ch == '$' {
+                // `{:0width$}` is the `0` flag followed by a `width$` parameter
+                if word.len() > 1 && word.starts_with('0') {
+                    self.out.push('0');
+                    word.remove(0);
+                }
+
+                let field_idx = self.resolve(&word, range)?;
+                let binding = self.use_field(field_idx, None, true);
+                self.out.push_str(&binding.to_string());
+                word.clear();
+            } else {
+                self.out.push_str(&word);
+                word.clear();
+            }
+
+            self.out.push(ch);
+        }
+
+        self.out.push_str(&word);
+
+        Ok(())
+    }
+
+    /// Find the index of the field referenced by `arg`
+    fn resolve(&self, arg: &str, range: &Range<usize>) -> syn::Result<usize> {
+        let found = if let Ok(idx) = arg.parse::<usize>() {
+            self.fields
+                .get(idx)
+                .filter(move |f| f.ident.is_none())
+                .map(move |_| idx)
+        } else {
+            self.fields
+                .iter()
+                .position(move |f| f.ident.is_some_and(move |i| i.unraw() == arg))
+        };
+
+        found.ok_or_else(move || {
+            let msg = if self.fields.is_empty() {
+                "Placeholders can't be used without any fields".to_owned()
+            } else {
+                format!("No field matching placeholder `{arg}`")
+            };
+
+            self.error(range.clone(), msg)
+        })
+    }
+
+    /// Register the field as an argument, returning its binding
+    fn use_field(
+        &mut self,
+        field_idx: usize,
+        trait_name: Option<&'static str>,
+        by_value: bool,
+    ) -> Ident {
+        let arg_idx = self.arg_idx(field_idx);
+
+        let arg = if arg_idx == self.args.len() {
+            let field = &self.fields[field_idx];
+            self.args.push(TemplateArg {
+                binding: format_ident!("__dd_field{}", field_idx),
+                member: match field.ident {
+                    Some(ident) => Member::Named(ident.clone()),
+                    None => Member::Unnamed(Index::from(field_idx)),
+                },
+                ty: field.ty.clone(),
+                by_value: false,
+                traits: Vec::new(),
+            });
+            self.args.last_mut().unwrap()
+        } else {
+            &mut self.args[arg_idx]
+        };
+
+        arg.by_value |= by_value;
+
+        if let Some(trait_name) = trait_name {
+            if !arg.traits.contains(&trait_name) {
+                arg.traits.push(trait_name);
+            }
+        }
+
+        arg.binding.clone()
+    }
+
+    /// Index of the field's entry in `self.args`; equal to its length if it's not there yet
+    fn arg_idx(&self, field_idx: usize) -> usize {
+        let binding = format_ident!("__dd_field{}", field_idx);
+        self.args
+            .iter()
+            .position(move |a| a.binding == binding)
+            .unwrap_or(self.args.len())
+    }
+
+    /// Create an error pointing at the given range of the template if the compiler supports it or
+    /// the whole template otherwise
+    fn error<M: std::fmt::Display>(&self, range: Range<usize>, msg: M) -> Error {
+        let token = self.lit.token();
+        let repr = token.to_string();
+
+        // Offsets only line up for plain, escape-free literals
+        let span = if repr.len() == self.src.len() + 2 && repr[1..repr.len() - 1] == *self.src {
+            token.subspan((range.start + 1)..=range.end)
+        } else {
+            None
+        };
+
+        Error::new(span.unwrap_or_else(move || self.lit.span()), msg)
+    }
+}
+
+/// Get the [`fmt`](::core::fmt) trait a format spec, e.g. `>8.2x`, formats its argument with
+fn trait_for_spec(spec: &str) -> &'static str {
+    if spec.ends_with("x?") || spec.ends_with("X?") {
+        return "Debug";
+    }
+
+    match spec.chars().last() {
+        Some('?') => "Debug",
+        Some('x') => "LowerHex",
+        Some('X') => "UpperHex",
+        Some('o') => "Octal",
+        Some('b') => "Binary",
+        Some('e') => "LowerExp",
+        Some('E') => "UpperExp",
+        Some('p') => "Pointer",
+        _ => "Display",
+    }
+}
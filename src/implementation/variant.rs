@@ -1,6 +1,10 @@
-use super::main_field::MainField;
+use super::main_field::{has_member, member_name, MainField};
+use super::opts::{ContainerOptions, Level};
+use super::template::Template;
 use proc_macro2::Ident;
+use syn::ext::IdentExt;
 use syn::spanned::Spanned;
+use syn::Error;
 
 #[derive(Copy, Clone)]
 pub(crate) enum Style {
@@ -11,34 +15,96 @@ pub(crate) enum Style {
 
 pub(crate) struct Variant {
     pub ident: Ident,
+    /// The identifier as it should be output, i.e. with `rename_all` applied
+    pub name: String,
     pub style: Style,
-    pub main_field: Option<MainField>,
+    pub main_fields: Vec<MainField>,
+    pub template: Option<Template>,
+    pub opts: ContainerOptions,
+
+    /// The field selected through the container's `field` or `path` option doesn't exist &
+    /// nothing else could be delegated to
+    pub lacks_selected_field: bool,
 }
 
 impl Variant {
-    pub fn from_syn(variant: syn::Variant, attr_name: &str) -> syn::Result<Self> {
-        let (style, main_field) = match variant.fields {
-            syn::Fields::Named(f) => {
-                let span = f.span();
-                (
-                    Style::Named,
-                    MainField::resolve_from_iter(f.named, attr_name, span)?,
-                )
-            }
-            syn::Fields::Unnamed(f) => {
-                let span = f.span();
-                (
-                    Style::Tuple,
-                    MainField::resolve_from_iter(f.unnamed, attr_name, span)?,
-                )
+    pub fn from_syn(
+        variant: syn::Variant,
+        attr_name: &str,
+        container_opts: &ContainerOptions,
+    ) -> syn::Result<Self> {
+        let opts = ContainerOptions::resolve(variant.attrs, attr_name, Level::Variant)?;
+
+        // Variant-level literals & templates take precedence over container-level ones
+        let fmt = if opts.literal.is_some() {
+            None
+        } else {
+            let container_fmt = container_opts
+                .fmt
+                .as_ref()
+                .filter(move |_| container_opts.literal.is_none());
+            opts.fmt.as_ref().or(container_fmt)
+        };
+        let template = match fmt {
+            Some(fmt) => Some(Template::parse(fmt, &variant.fields)?),
+            None => None,
+        };
+
+        let skip_fields = template.is_some()
+            || opts.literal.is_some()
+            || container_opts.literal.is_some()
+            || opts.method.is_some()
+            || container_opts.method.is_some();
+
+        let multiple = opts.join.is_some()
+            || container_opts.join.is_some()
+            || opts.named
+            || container_opts.named;
+        let alternate = opts
+            .alternate_field()
+            .or_else(move || container_opts.alternate_field());
+        let selected = opts.selection().or_else(move || container_opts.selection());
+
+        if let Some(selection) = opts.selection().filter(|_| !skip_fields) {
+            if !has_member(&variant.fields, selection.field) {
+                let msg = format!("No field {} to delegate to", member_name(selection.field));
+                return Err(Error::new_spanned(selection.field, msg));
             }
-            syn::Fields::Unit => (Style::Unit, None),
+        }
+
+        let span = variant.fields.span();
+        let (style, main_fields) = match variant.fields {
+            syn::Fields::Named(_) if skip_fields => (Style::Named, Some(Vec::new())),
+            syn::Fields::Unnamed(_) if skip_fields => (Style::Tuple, Some(Vec::new())),
+            syn::Fields::Named(f) => (
+                Style::Named,
+                MainField::resolve_from_iter(
+                    f.named, attr_name, span, multiple, alternate, selected,
+                )?,
+            ),
+            syn::Fields::Unnamed(f) => (
+                Style::Tuple,
+                MainField::resolve_from_iter(
+                    f.unnamed, attr_name, span, multiple, alternate, selected,
+                )?,
+            ),
+            syn::Fields::Unit => (Style::Unit, Some(Vec::new())),
+        };
+
+        let ident = variant.ident.unraw().to_string();
+        let name = match container_opts.rename_all {
+            Some(rule) => rule.apply(&ident),
+            None => ident,
         };
 
         Ok(Self {
+            name,
             ident: variant.ident,
             style,
-            main_field,
+            lacks_selected_field: main_fields.is_none(),
+            main_fields: main_fields.unwrap_or_default(),
+            template,
+            opts,
         })
     }
 }
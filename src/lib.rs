@@ -131,6 +131,47 @@
 //!
 //! </details>
 
+//! <details><summary>Joining multiple fields</summary>
+//!
+//! Multiple fields can be marked when a `join` separator is provided on the container or variant. Each field
+//! is delegated to in order with the separator written in-between.
+//!
+//! ```
+//! # use delegate_display::*;
+//! # use std::ops::Deref;
+//! #
+//! struct Name(&'static str); // Derefs to `str`
+//! # impl Deref for Name {
+//! #   type Target = str;
+//! #   fn deref(&self) -> &Self::Target {
+//! #     self.0
+//! #   }
+//! # }
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(join = " ")]
+//! struct FullName<T> {
+//!   #[ddisplay(delegate_to(str))]
+//!   first: Name,
+//!   id: u32,
+//!   #[ddisplay]
+//!   last: T,
+//! }
+//!
+//! #[derive(DelegateDebug)]
+//! enum MyEnum {
+//!   #[ddebug(join = ", ")]
+//!   Pair(#[ddebug] u8, #[ddebug] &'static str, u8),
+//!   Single(u8),
+//! }
+//!
+//! assert_eq!(format!("{}", FullName { first: Name("John"), id: 1, last: "Doe" }), "John Doe");
+//! assert_eq!(format!("{:?}", MyEnum::Pair(1, "two", 3)), "1, \"two\"");
+//! assert_eq!(format!("{:?}", MyEnum::Single(4)), "4");
+//! ```
+//!
+//! </details>
+
 //! <details><summary>Empty structs</summary>
 //!
 //! ```
@@ -152,6 +193,140 @@
 //!
 //! </details>
 
+//! <details><summary>Unit names</summary>
+//!
+//! Structs and enum variants with nothing to delegate to can output their name instead of an empty string
+//! through the `unit = name` option. It can be set on the container or overridden per variant with
+//! `unit = name` or `unit = empty`. Raw identifiers are output without their `r#` prefix.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDebug)]
+//! #[ddebug(unit = name)]
+//! struct Foo;
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(unit = name)]
+//! enum MyEnum {
+//!   Bar,
+//!   Qux(u8),
+//!   #[ddebug(unit = empty)]
+//!   Baz,
+//! }
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(unit = name)]
+//! #[allow(non_camel_case_types)]
+//! enum Keyword {
+//!   r#type,
+//! }
+//!
+//! assert_eq!(format!("{:?}", Foo), "Foo");
+//! assert_eq!(format!("{:?}", MyEnum::Bar), "Bar");
+//! assert_eq!(format!("{:?}", MyEnum::Qux(1)), "1");
+//! assert_eq!(format!("{:?}", MyEnum::Baz), "");
+//! assert_eq!(format!("{:?}", Keyword::r#type), "type");
+//! ```
+//!
+//! The output names can be re-cased with the container-level `rename_all` option, which accepts
+//! `"lowercase"`, `"UPPERCASE"`, `"PascalCase"`, `"camelCase"`, `"snake_case"`, `"SCREAMING_SNAKE_CASE"`,
+//! `"kebab-case"` and `"SCREAMING-KEBAB-CASE"`. Literal outputs are unaffected.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(unit = name, rename_all = "kebab-case")]
+//! enum Status {
+//!   NotFound,
+//!   HTTPError,
+//!   #[ddisplay("ok!")]
+//!   Ok,
+//! }
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(unit = name, rename_all = "SCREAMING_SNAKE_CASE")]
+//! struct EndOfFile;
+//!
+//! assert_eq!(format!("{}", Status::NotFound), "not-found");
+//! assert_eq!(format!("{}", Status::HTTPError), "http-error");
+//! assert_eq!(format!("{}", Status::Ok), "ok!");
+//! assert_eq!(format!("{}", EndOfFile), "END_OF_FILE");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Literal output</summary>
+//!
+//! Enum variants and structs can output a fixed string instead of delegating by passing it as the
+//! attribute's first argument (or as `literal = "..."`). The string is written via
+//! [`Formatter::pad`](::core::fmt::Formatter::pad) so width & alignment apply.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! enum MyError {
+//!   #[ddisplay("not found")]
+//!   NotFound,
+//!   #[ddisplay("timed out")]
+//!   TimedOut { after_ms: u64, retries: u8 },
+//!   Other(String),
+//! }
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay("eof")]
+//! struct Eof;
+//!
+//! assert_eq!(format!("{}", MyError::NotFound), "not found");
+//! assert_eq!(format!("{}", MyError::TimedOut { after_ms: 500, retries: 3 }), "timed out");
+//! assert_eq!(format!("{}", MyError::Other("oops".into())), "oops");
+//! assert_eq!(format!("[{:>5}]", Eof), "[  eof]");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Format templates</summary>
+//!
+//! Containers and variants can be formatted through a template with the `fmt` option. Placeholders can reference
+//! named fields, tuple positions or be left implicit, and accept the usual format specs.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(fmt = "{code}: {message}")]
+//! struct Error {
+//!   code: u16,
+//!   message: &'static str,
+//! }
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(fmt = "[{value:>8.2}]")]
+//! struct Price { value: f64 }
+//!
+//! #[derive(DelegateDisplay)]
+//! enum Shape<T> {
+//!   #[ddisplay(fmt = "{0}x{1}")]
+//!   Rect(T, T),
+//!   #[ddisplay(fmt = "r={radius:#x} {{round}}")]
+//!   Circle { radius: u8 },
+//!   #[ddisplay(fmt = "({}, {:?})")]
+//!   Pair(u8, &'static str),
+//!   Other(T),
+//! }
+//!
+//! assert_eq!(format!("{}", Error { code: 404, message: "not found" }), "404: not found");
+//! assert_eq!(format!("{}", Price { value: 3.14159 }), "[    3.14]");
+//! assert_eq!(format!("{}", Shape::Rect(2, 3)), "2x3");
+//! assert_eq!(format!("{}", Shape::<u8>::Circle { radius: 255 }), "r=0xff {round}");
+//! assert_eq!(format!("{}", Shape::<u8>::Pair(1, "a")), "(1, \"a\")");
+//! assert_eq!(format!("{}", Shape::Other(5)), "5");
+//! ```
+//!
+//! </details>
+
 //! <details><summary>Typed delegations</summary>
 //!
 //! Can be useful for further prettifying the output.
@@ -182,6 +357,651 @@
 //!
 //! </details>
 
+//! <details><summary>Custom formatter functions</summary>
+//!
+//! The `with` option formats the field through a function with the signature
+//! `fn(&FieldType, &mut Formatter<'_>) -> fmt::Result` instead of the field's own implementation. It can be set
+//! on the container, a variant or a field and no generic bounds get inferred for it.
+//!
+//! ```
+//! # use delegate_display::*;
+//! # use core::fmt;
+//! #
+//! fn fmt_millis(value: &u64, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+//!   write!(f, "{}.{:03}s", value / 1000, value % 1000)
+//! }
+//!
+//! fn fmt_len<T>(value: &Vec<T>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+//!   write!(f, "{} items", value.len())
+//! }
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(with = fmt_millis)]
+//! struct Elapsed(u64);
+//!
+//! #[derive(DelegateDisplay)]
+//! enum Payload<T> {
+//!   #[ddisplay(with = fmt_len)]
+//!   Items(Vec<T>),
+//!   Timed(u8, #[ddisplay(with = fmt_millis)] u64),
+//! }
+//!
+//! struct NoDisplay;
+//!
+//! assert_eq!(format!("{}", Elapsed(1500)), "1.500s");
+//! assert_eq!(format!("{}", Payload::Items(vec![NoDisplay, NoDisplay])), "2 items");
+//! assert_eq!(format!("{}", Payload::<NoDisplay>::Timed(1, 25)), "0.025s");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Delegating to methods</summary>
+//!
+//! The `method` option delegates to a method's return value instead of a field. On containers and variants the
+//! method is called on `self`; on fields it's called on the field. The return type is inferred unless
+//! `delegate_to` is provided.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(method = name)]
+//! struct User { first: &'static str, id: u32 }
+//! impl User {
+//!   fn name(&self) -> &str {
+//!     self.first
+//!   }
+//! }
+//!
+//! #[derive(DelegateDebug)]
+//! enum Collection {
+//!   #[ddebug(method = describe)]
+//!   Empty,
+//!   Items(#[ddebug(method = len)] Vec<u8>),
+//! }
+//! impl Collection {
+//!   fn describe(&self) -> String {
+//!     "nothing here".into()
+//!   }
+//! }
+//!
+//! assert_eq!(format!("{}", User { first: "Alice", id: 1 }), "Alice");
+//! assert_eq!(format!("{:?}", Collection::Empty), "\"nothing here\"");
+//! assert_eq!(format!("{:?}", Collection::Items(vec![1, 2, 3])), "3");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Cross-trait delegation</summary>
+//!
+//! The `via` option implements the derived trait by calling a different [`fmt`](::core::fmt) trait on the
+//! delegation target. Inferred generic bounds follow the called trait.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! struct DisplayOnly;
+//! impl core::fmt::Display for DisplayOnly {
+//!   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+//!     f.write_str("displayed")
+//!   }
+//! }
+//!
+//! #[derive(DelegateDebug, DelegateDisplay)]
+//! #[ddebug(via = Display)]
+//! struct MyError<T>(T);
+//!
+//! #[derive(DelegateDisplay)]
+//! struct Hex(#[ddisplay(via = LowerHex)] u32);
+//!
+//! assert_eq!(format!("{:?}", MyError(DisplayOnly)), "displayed");
+//! assert_eq!(format!("{}", Hex(255)), "ff");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Prefixes & suffixes</summary>
+//!
+//! `prefix` and `suffix` wrap the delegated output and can be set on containers, variants and fields. If the
+//! formatter has a width set, the decorated output gets padded as a whole.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(suffix = " ms")]
+//! struct Millis(u64);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(prefix = "'", suffix = "'")]
+//! struct Quoted(String);
+//!
+//! #[derive(DelegateDisplay)]
+//! enum Event {
+//!   #[ddisplay(prefix = "started at ")]
+//!   Start(u64),
+//!   Stop { #[ddisplay(prefix = "#")] id: u8, reason: &'static str },
+//! }
+//!
+//! assert_eq!(format!("{}", Millis(150)), "150 ms");
+//! assert_eq!(format!("{:>8}|", Millis(150)), "  150 ms|");
+//! assert_eq!(format!("{:-^7.1}", Quoted("xyz".into())), "--'x'--");
+//! assert_eq!(format!("{}", Event::Start(9)), "started at 9");
+//! assert_eq!(format!("{}", Event::Stop { id: 3, reason: "done" }), "#3");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Padding</summary>
+//!
+//! Many types ignore the formatter's width, fill & alignment. The `pad` option renders the whole output into a
+//! stack buffer first and pads it with [`Formatter::pad`](::core::fmt::Formatter::pad), so they apply regardless.
+//! It works for every derived trait and in `no_std` crates: output too long for the buffer gets formatted twice,
+//! once to measure it and once to write it, unless the `alloc` feature is enabled, in which case it gets rendered
+//! on the heap instead. On fields, `pad` only pads that field's output.
+//!
+//! ```
+//! # use delegate_display::*;
+//! # use core::fmt;
+//! #
+//! struct Stubborn;
+//! impl fmt::Display for Stubborn {
+//!   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+//!     f.write_str("abc") // Ignores width
+//!   }
+//! }
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(pad)]
+//! struct Padded(Stubborn);
+//!
+//! #[derive(DelegateDisplay)]
+//! struct Unpadded(Stubborn);
+//!
+//! assert_eq!(format!("{:>6}", Padded(Stubborn)), "   abc");
+//! assert_eq!(format!("{:-^7}", Padded(Stubborn)), "--abc--");
+//! assert_eq!(format!("{:>6}", Unpadded(Stubborn)), "abc");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Optional fields</summary>
+//!
+//! Delegating to an [`Option`] delegates to its inner value if it's [`Some`]. This is inferred from fields whose
+//! type is spelled `Option<T>` for every trait except [`Debug`](::core::fmt::Debug), which [`Option`] already
+//! implements; the `option` flag enables it explicitly, e.g. for type aliases or method return values. Inferred
+//! generic bounds get placed on `T`.
+//!
+//! [`None`] writes nothing by default. The `none` option can instead write a placeholder (`none = "-"`) or fail
+//! with a [`fmt::Error`](::core::fmt::Error) (`none = error`).
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(none = "-")]
+//! struct Maybe<T>(Option<T>);
+//!
+//! #[derive(DelegateDisplay)]
+//! struct Blank(Option<u8>);
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(option, none = "nothing")]
+//! struct Dbg(Option<&'static str>);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(none = error)]
+//! struct Strict(Option<u8>);
+//!
+//! assert_eq!(format!("{}", Maybe(Some(1))), "1");
+//! assert_eq!(format!("{:>3}", Maybe::<u8>(None)), "  -");
+//! assert_eq!(format!("{}", Blank(None)), "");
+//! assert_eq!(format!("{:?}", Dbg(Some("x"))), "\"x\"");
+//! assert_eq!(format!("{:?}", Dbg(None)), "nothing");
+//!
+//! let mut out = String::new();
+//! assert!(std::fmt::write(&mut out, format_args!("{}", Strict(None))).is_err());
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Iterables</summary>
+//!
+//! The `iter` flag formats every item of an iterable field through the derived trait, separated by `sep`
+//! (`", "` by default) and wrapped in `open` & `close`. Maps get formatted as key-value pairs if `kv_sep` is set.
+//! The inferred generic bounds require `&Field` to implement [`IntoIterator`] with items that implement the trait.
+//!
+//! ```
+//! # use delegate_display::*;
+//! # use std::collections::BTreeMap;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(iter, open = "[", close = "]")]
+//! struct List<T>(Vec<T>);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(iter, sep = "; ", kv_sep = "=")]
+//! struct Params<K, V>(BTreeMap<K, V>);
+//!
+//! #[derive(DelegateLowerHex)]
+//! #[dlhex(iter, sep = " ")]
+//! struct Bytes<'a>(&'a [u8]);
+//!
+//! let params = BTreeMap::from([("a", 1), ("b", 2)]);
+//!
+//! assert_eq!(format!("{}", List(vec![1, 2, 3])), "[1, 2, 3]");
+//! assert_eq!(format!("{:.1}", List(vec![1.25, 2.0])), "[1.2, 2.0]");
+//! assert_eq!(format!("{}", Params(params)), "a=1; b=2");
+//! assert_eq!(format!("{:02x}", Bytes(&[1, 255])), "01 ff");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Redaction</summary>
+//!
+//! The `redact` option writes a mask instead of delegating. It can be set on containers, variants & fields.
+//!
+//! - `redact` writes `***`
+//! - `redact = "<hidden>"` or `redact(mask = "<hidden>")` writes a custom mask
+//! - `redact(type_name)` writes the name of the type instead of the mask: the container's if set on the container, or
+//!   the redacted field's if set on a variant or field
+//! - `redact(len)` appends the `len()` of the delegation target
+//! - `redact(prefix = N)` reveals the first `N` characters of the delegated output before the mask
+//!
+//! Redacted fields don't get any inferred generic bounds unless they reveal a prefix, which requires formatting them.
+//! [`Option`] fields get unwrapped for `prefix` & `len` as they would be without `redact`, writing the `none` output
+//! for [`None`]; a bare mask gets written either way. The caller's width pads the whole redacted output, revealed
+//! prefix & length included.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! struct NotDebug;
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(redact)]
+//! struct Password<T>(T);
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(redact(type_name))]
+//! struct ApiKey(String);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(redact(prefix = 3, len))]
+//! struct Token(String);
+//!
+//! #[derive(DelegateDebug)]
+//! enum Credential {
+//!   #[ddebug(redact = "<hidden>")]
+//!   Secret(String),
+//!   Public(String),
+//! }
+//!
+//! assert_eq!(format!("{:?}", Password(NotDebug)), "***");
+//! assert_eq!(format!("{:?}", ApiKey("hunter2".into())), "ApiKey");
+//! assert_eq!(format!("{}", Token("sk-abcdef".into())), "sk-*** (len 9)");
+//! assert_eq!(format!("{:>16}", Token("sk-abcdef".into())), "  sk-*** (len 9)");
+//! assert_eq!(format!("{:?}", Credential::Secret("hunter2".into())), "<hidden>");
+//! assert_eq!(format!("{:?}", Credential::Public("alice".into())), "\"alice\"");
+//! ```
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(redact(prefix = 2), none = "unset")]
+//! struct OptionalToken(Option<String>);
+//!
+//! assert_eq!(format!("{}", OptionalToken(Some("sk-abcdef".into()))), "sk***");
+//! assert_eq!(format!("{}", OptionalToken(None)), "unset");
+//!
+//! #[derive(DelegateDebug)]
+//! struct Config {
+//!   #[ddebug(redact(type_name))]
+//!   key: ApiKey,
+//! }
+//!
+//! assert_eq!(format!("{:?}", Config { key: ApiKey("hunter2".into()) }), "ApiKey");
+//! #
+//! # #[derive(DelegateDebug)]
+//! # struct ApiKey(String);
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Truncation</summary>
+//!
+//! `max_len = N` cuts the delegated output off after `N` characters and writes an ellipsis, `…` by default or the
+//! `ellipsis` option if set, if anything got cut. The ellipsis doesn't count towards the limit, but it does count
+//! towards the caller's width: the truncated output & the ellipsis get padded as a whole. Nothing gets allocated: the
+//! output is streamed through a writer that stops once the limit's reached.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay, DelegateDebug)]
+//! #[ddisplay(max_len = 5)]
+//! #[ddebug(max_len = 4, ellipsis = "...")]
+//! struct UserInput(String);
+//!
+//! assert_eq!(format!("{}", UserInput("hello".into())), "hello");
+//! assert_eq!(format!("{}", UserInput("hello world".into())), "hello…");
+//! assert_eq!(format!("{:?}", UserInput("hello".into())), "\"hel...");
+//! assert_eq!(format!("{:>8}", UserInput("hello world".into())), "  hello…");
+//! assert_eq!(format!("{:-<8}", UserInput("hi".into())), "hi------");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Escaping</summary>
+//!
+//! `escape` streams the delegated output through an escaping writer without allocating:
+//!
+//! - `escape = "html"` escapes `&`, `<`, `>`, `"` & `'` as HTML entities
+//! - `escape = "json"` escapes the output as a JSON string
+//! - `escape = "shell"` escapes the output as a POSIX shell word
+//! - `escape = "debug"` escapes the output the same way [`str`]'s [`Debug`](::core::fmt::Debug) implementation
+//!   does
+//!
+//! `quote = none | single | double` sets the quotes the output gets wrapped in. JSON and debug output get double
+//! quotes by default, shell output gets single quotes and HTML output doesn't get any. JSON strings can't be
+//! single-quoted, so `quote = single` is rejected with JSON escaping.
+//!
+//! The caller's width pads the escaped & quoted output as a whole. Only the `#`, `+` & precision flags get passed on
+//! to the delegated call: `{:x?}`, `-` & `0` aren't forwarded through the escaping writer.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(escape = "html")]
+//! struct Html(&'static str);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(escape = "json")]
+//! struct Json(&'static str);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(escape = "shell")]
+//! struct ShellArg(&'static str);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(escape = "debug", quote = single)]
+//! struct Quoted(&'static str);
+//!
+//! assert_eq!(format!("{}", Html("<b>Tom & Jerry</b>")), "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;");
+//! assert_eq!(format!("{}", Json("say \"hi\"\n")), r#""say \"hi\"\n""#);
+//! assert_eq!(format!("{}", ShellArg("it's")), r"'it'\''s'");
+//! assert_eq!(format!("{}", Quoted("it's\t")), r"'it\'s\t'");
+//! assert_eq!(format!("{:>8}", Html("a<b")), "  a&lt;b");
+//! assert_eq!(format!("{:*<6}", Json("hi")), r#""hi"**"#);
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Case conversion</summary>
+//!
+//! `case = "lower" | "upper"` converts the delegated output's case as it gets written, following the same Unicode
+//! rules as [`str::to_lowercase`] & [`str::to_uppercase`], e.g. `ß` becomes `SS`. Combined with `escape`, the output
+//! gets converted before it's escaped. The caller's width pads the converted output as a whole.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateFmt)]
+//! #[dfmt(ddisplay(case = "upper"), ddebug)]
+//! struct Shout(&'static str);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(case = "lower")]
+//! struct Whisper(&'static str);
+//!
+//! assert_eq!(format!("{}", Shout("straße")), "STRASSE");
+//! assert_eq!(format!("{:?}", Shout("straße")), r#""straße""#);
+//! assert_eq!(format!("{}", Whisper("ΟΔΥΣΣΕΥΣ")), "οδυσσευς");
+//! assert_eq!(format!("{:>8}", Whisper("AB")), "      ab");
+//! assert_eq!(format!("{:-^11}", Shout("straße")), "--STRASSE--");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Formatter overrides</summary>
+//!
+//! `width`, `fill`, `align = left | right | center`, `alternate`, `sign_plus` & `precision` override the caller's
+//! format spec when formatting the delegation target. `fill = '0'` without an `align` is the sign-aware zero padding
+//! flag, as in `{:010}`, and any other `fill` defaults to right alignment. Flags that aren't overridden are taken
+//! from the caller. If `width` isn't overridden, the caller's width, alignment & `0` flag are passed on to the
+//! delegation target along with the overrides, so `{:08x}` zero-pads a number after its `0x` prefix. `fill` & `align`
+//! without a `width` pad the output to the caller's width, taking precedence over the caller's fill, alignment & `0`
+//! flag.
+//!
+//! With the `fallback` flag, `width` & `precision` only apply if the caller didn't specify their own.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateLowerHex)]
+//! #[dlhex(alternate, width = 10, fill = '0')]
+//! struct Hex(u32);
+//!
+//! #[derive(DelegateLowerHex)]
+//! #[dlhex(alternate)]
+//! struct Byte(u8);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(fill = '*')]
+//! struct Stars(u8);
+//!
+//! #[derive(DelegateFmt)]
+//! #[dfmt(ddisplay(precision = 2, fallback), ddebug)]
+//! struct Price(f64);
+//!
+//! #[derive(DelegateDisplay)]
+//! struct Delta {
+//!   #[ddisplay(sign_plus)]
+//!   value: i32,
+//! }
+//!
+//! assert_eq!(format!("{:x}", Hex(42)), "0x0000002a");
+//! assert_eq!(format!("{:08x}", Byte(255)), "0x0000ff");
+//! assert_eq!(format!("{:8x}", Byte(255)), "    0xff");
+//! assert_eq!(format!("{:8}", Price(1.0)), "    1.00");
+//! assert_eq!(format!("{}", Stars(1)), "1");
+//! assert_eq!(format!("{:6}", Stars(1)), "*****1");
+//! assert_eq!(format!("{:<6}", Stars(1)), "1*****");
+//! assert_eq!(format!("{}", Price(1.5)), "1.50");
+//! assert_eq!(format!("{:.1}", Price(1.5)), "1.5");
+//! assert_eq!(format!("{:?}", Price(1.5)), "1.5");
+//! assert_eq!(format!("{:>4}", Delta { value: 3 }), "  +3");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Alternate targets</summary>
+//!
+//! `alternate = field` delegates to a different field when formatting with the `#` flag. The field can be named or,
+//! for tuple structs & variants, referenced by its index. It doesn't need to be marked and doesn't count towards the
+//! fields needing a marker, but it can be given a marker to provide its own options, e.g. `delegate_to`. Set on an
+//! enum, the option applies to every variant with fields unless a variant overrides it.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(alternate = message)]
+//! struct Error {
+//!   code: u16,
+//!   message: &'static str,
+//! }
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(alternate = 1)]
+//! enum Status {
+//!   Ok(u8, &'static str),
+//!   #[ddisplay(alternate = 0)]
+//!   Failed(&'static str, #[ddisplay] u8),
+//! }
+//!
+//! let err = Error { code: 404, message: "Not found" };
+//! assert_eq!(format!("{err}"), "404");
+//! assert_eq!(format!("{err:#}"), "Not found");
+//!
+//! assert_eq!(format!("{}", Status::Ok(0, "fine")), "0");
+//! assert_eq!(format!("{:#}", Status::Failed("broken", 1)), "broken");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Named output</summary>
+//!
+//! The `named` flag writes the struct/variant name around the delegated output through
+//! [`debug_tuple`](::core::fmt::Formatter::debug_tuple) or [`debug_struct`](::core::fmt::Formatter::debug_struct),
+//! depending on whether the field is named, so `{:#?}` pretty-prints it. The field's value still goes through
+//! `delegate_to` & any other options. Variants without fields output their name unless the `unit` option says
+//! otherwise.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDebug)]
+//! #[ddebug(named, delegate_to(str))]
+//! struct Typed(String);
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(named)]
+//! enum Key {
+//!   Id(#[ddebug(delegate_to(str))] String),
+//!   Pair { key: (u8, u8) },
+//!   Missing,
+//! }
+//!
+//! assert_eq!(format!("{:?}", Typed("foo".into())), r#"Typed("foo")"#);
+//! assert_eq!(format!("{:?}", Key::Id("foo".into())), r#"Id("foo")"#);
+//! assert_eq!(format!("{:?}", Key::Pair { key: (1, 2) }), "Pair { key: (1, 2) }");
+//! assert_eq!(format!("{:#?}", Key::Pair { key: (1, 2) }), "Pair {\n    key: (\n        1,\n        2,\n    ),\n}");
+//! assert_eq!(format!("{:?}", Key::Missing), "Missing");
+//! ```
+//!
+//! In `named` mode, multiple fields can be marked to output only those, e.g. to hide large buffers. Fields can be
+//! renamed with `rename = "..."` and omitted fields are indicated with a trailing `..`.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDebug)]
+//! #[ddebug(named)]
+//! struct Connection {
+//!   #[ddebug] host: &'static str,
+//!   #[ddebug(rename = "port_no")] port: u16,
+//!   buffer: Vec<u8>,
+//! }
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(named)]
+//! enum Frame {
+//!   Data(#[ddebug] u32, Vec<u8>),
+//!   Ping(u32),
+//! }
+//!
+//! let conn = Connection { host: "localhost", port: 80, buffer: vec![0; 4096] };
+//! assert_eq!(format!("{conn:?}"), r#"Connection { host: "localhost", port_no: 80, .. }"#);
+//! assert_eq!(format!("{:?}", Frame::Data(1, vec![0; 4096])), "Data(1, ..)");
+//! assert_eq!(format!("{:?}", Frame::Ping(2)), "Ping(2)");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Variant prefixes</summary>
+//!
+//! `variant_prefix` on an enum writes a prefix before the output of every variant that delegates, with `{}`
+//! replaced by the variant's name after any `rename_all`. Variants can set their own `variant_prefix`.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(variant_prefix = "{}: ", rename_all = "snake_case")]
+//! enum Error {
+//!   Io(&'static str),
+//!   #[ddisplay(variant_prefix = "[{}] ")]
+//!   ParseInt(u8),
+//!   #[ddisplay("unknown error")]
+//!   Unknown,
+//! }
+//!
+//! assert_eq!(format!("{}", Error::Io("disk full")), "io: disk full");
+//! assert_eq!(format!("{}", Error::ParseInt(1)), "[parse_int] 1");
+//! assert_eq!(format!("{}", Error::Unknown), "unknown error");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Field selection</summary>
+//!
+//! `field = name` or, for tuple structs & variants, `field = index` selects the field to delegate to without marking
+//! it, e.g. when the fields come from another macro. On an enum, it applies to every variant that has the field;
+//! variants without it need a single field or a marked one. Field markers take precedence over the selection.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(field = 1)]
+//! struct Labelled(u32, &'static str);
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(field = message)]
+//! enum Event {
+//!   Info { code: u16, message: &'static str },
+//!   Warn { code: u16, message: &'static str },
+//!   Raw(&'static str),
+//! }
+//!
+//! assert_eq!(format!("{}", Labelled(1, "one")), "one");
+//! assert_eq!(format!("{}", Event::Info { code: 1, message: "started" }), "started");
+//! assert_eq!(format!("{}", Event::Raw("raw")), "raw");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Nested fields</summary>
+//!
+//! `path = meta.id.0` delegates to a nested field. On a struct, enum or variant, the first field in the path gets
+//! selected the same way as with `field`; on a field, the path continues from that field. As nested types can't be
+//! seen by the macro, the target type comes from `delegate_to` or gets inferred, so generic targets need explicit
+//! `bounds`.
+//!
+//! ```
+//! # use delegate_display::*;
+//! #
+//! struct Id(u32);
+//! struct Meta { id: Id, source: &'static str }
+//!
+//! #[derive(DelegateDisplay)]
+//! #[ddisplay(path = meta.id.0)]
+//! struct Event {
+//!   meta: Meta,
+//!   payload: Vec<u8>,
+//! }
+//!
+//! #[derive(DelegateDisplay)]
+//! struct Source {
+//!   #[ddisplay(path = source)]
+//!   meta: Meta,
+//! }
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(path = 0.0, bounds(T: core::fmt::Debug))]
+//! struct Wrapped<T>((T,));
+//!
+//! let meta = || Meta { id: Id(42), source: "sensor" };
+//! assert_eq!(format!("{}", Event { meta: meta(), payload: vec![] }), "42");
+//! assert_eq!(format!("{}", Source { meta: meta() }), "sensor");
+//! assert_eq!(format!("{:?}", Wrapped(("x",))), r#""x""#);
+//! ```
+//!
+//! </details>
+
 //! <details><summary>Custom generic bounds</summary>
 //!
 //! ```
@@ -215,6 +1035,95 @@
 //!
 //! </details>
 
+//! <details><summary>Per-variant options</summary>
+//!
+//! `delegate_to` and `bounds` can also be set on individual enum variants, overriding the container's
+//! options for that variant only.
+//!
+//! ```
+//! # use delegate_display::*;
+//! # use std::ops::Deref;
+//! #
+//! struct StrWrapper(&'static str); // Derefs to `str`
+//! # impl Deref for StrWrapper {
+//! #   type Target = str;
+//! #   fn deref(&self) -> &Self::Target {
+//! #     self.0
+//! #   }
+//! # }
+//! struct U8Wrapper(u8); // Derefs to `u8`
+//! # impl Deref for U8Wrapper {
+//! #   type Target = u8;
+//! #   fn deref(&self) -> &Self::Target {
+//! #     &self.0
+//! #   }
+//! # }
+//! struct Generic<T>(T); // Derefs to `T`
+//! # impl<T> Deref for Generic<T> {
+//! #   type Target = T;
+//! #   fn deref(&self) -> &Self::Target {
+//! #     &self.0
+//! #   }
+//! # }
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(delegate_to(str))]
+//! enum MyEnum<T> {
+//!   Str(StrWrapper), // uses the container's `delegate_to`
+//!   #[ddebug(delegate_to(u8))]
+//!   Num(U8Wrapper),
+//!   #[ddebug(bounds(T: core::fmt::Debug), delegate_to(T))]
+//!   Generic(Generic<T>),
+//! }
+//!
+//! assert_eq!(format!("{:?}", MyEnum::<()>::Str(StrWrapper("foo"))), "\"foo\"");
+//! assert_eq!(format!("{:?}", MyEnum::<()>::Num(U8Wrapper(10))), "10");
+//! assert_eq!(format!("{:?}", MyEnum::Generic(Generic(true))), "true");
+//! ```
+//!
+//! </details>
+
+//! <details><summary>Field options</summary>
+//!
+//! Field markers accept the same options as the container, taking precedence over both
+//! container and variant options. Options that only shape the container or variant as a whole, i.e. `unit`,
+//! `literal`, `rename_all`, `fmt`, `join`, `named`, `variant_prefix`, `field` & `alternate = field`, are rejected on
+//! fields. `rename_all` & `rename` are rejected on variants, `rename` on containers and `variant_prefix` on structs.
+//!
+//! ```
+//! # use delegate_display::*;
+//! # use std::ops::Deref;
+//! #
+//! #[derive(Debug)]
+//! struct Wrapper(&'static str); // Derefs to `str`
+//! # impl Deref for Wrapper {
+//! #   type Target = str;
+//! #   fn deref(&self) -> &Self::Target {
+//! #     self.0
+//! #   }
+//! # }
+//!
+//! #[derive(DelegateDebug)]
+//! struct MyStruct {
+//!   id: u8,
+//!   #[ddebug(delegate_to(str))]
+//!   name: Wrapper,
+//! }
+//!
+//! #[derive(DelegateDebug)]
+//! #[ddebug(delegate_to(str))]
+//! enum MyEnum {
+//!   Typed(Wrapper),
+//!   Base(#[ddebug(delegate_to(Wrapper))] Wrapper),
+//! }
+//!
+//! assert_eq!(format!("{:?}", MyStruct { id: 1, name: Wrapper("foo") }), "\"foo\"");
+//! assert_eq!(format!("{:?}", MyEnum::Typed(Wrapper("bar"))), "\"bar\"");
+//! assert_eq!(format!("{:?}", MyEnum::Base(Wrapper("qux"))), "Wrapper(\"qux\")");
+//! ```
+//!
+//! </details>
+
 //! <details><summary>Multiple traits at once</summary>
 //!
 //! Instead of re-parsing your struct/enum multiple times, you can instead derive `DelegateFmt`.
@@ -275,10 +1184,73 @@
 //!
 //! ```compile_fail
 //! #[derive(delegate_display::DelegateDebug)]
+//! struct TooManyMarked {
+//!   #[ddebug] foo: u8,
+//!   #[ddebug] bar: u8, // ERR: Multiple fields marked without a `join` separator
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! #[derive(delegate_display::DelegateDebug)]
 //! union Foo { bar: u8 } // Unions are not supported
 //! ```
 //!
 //! ```compile_fail
+//! #[derive(delegate_display::DelegateDisplay)]
+//! #[ddisplay(fmt = "{code}: {msg}")] // ERR: No field matching placeholder `msg`
+//! struct Error { code: u16, message: String }
+//! ```
+//!
+//! ```compile_fail
+//! #[derive(delegate_display::DelegateDisplay)]
+//! #[ddisplay(fmt = "{0}-{2}")] // ERR: No field matching placeholder `2`
+//! struct Pair(u8, u8);
+//! ```
+//!
+//! ```compile_fail
+//! #[derive(delegate_display::DelegateDisplay)]
+//! #[ddisplay(field = message)] // ERR: No field named `message` in variant(s) `Timeout`
+//! enum Error {
+//!   Io { code: u16, message: String },
+//!   Timeout(u64, u64),
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! #[derive(delegate_display::DelegateDisplay)]
+//! #[ddisplay(alternate = msg)] // ERR: No field named `msg` to use as the alternate
+//! struct Error { code: u16, message: String }
+//! ```
+//!
+//! ```compile_fail
+//! #[derive(delegate_display::DelegateDisplay)]
+//! struct Greeting {
+//!   #[ddisplay(rename_all = "snake_case")] // ERR: `rename_all` has no effect on a field
+//!   name: String,
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! #[derive(delegate_display::DelegateDisplay)]
+//! enum Status {
+//!   #[ddisplay(rename = "ok")] // ERR: `rename` has no effect on a variant
+//!   Ok(u8),
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! #[derive(delegate_display::DelegateDisplay)]
+//! #[ddisplay(variant_prefix = "{}: ")] // ERR: `variant_prefix` has no effect on a struct
+//! struct Status(u8);
+//! ```
+//!
+//! ```compile_fail
+//! #[derive(delegate_display::DelegateDisplay)]
+//! #[ddisplay(escape = "json", quote = single)] // ERR: JSON strings can't be single-quoted
+//! struct Json(String);
+//! ```
+//!
+//! ```compile_fail
 //! # use delegate_display::*;
 //! #
 //! struct NonDebug;
@@ -335,6 +1307,9 @@ macro_rules! alias {
         )+
 
         impl implementation::Alias<'static> {
+            /// Every derivable [`fmt`](::core::fmt) trait
+            const ALL: &'static [implementation::Alias<'static>] = &[$(implementation::Alias::$attr_name),+];
+
             $(
                 #[allow(non_upper_case_globals)]
                 const $attr_name: implementation::Alias<'static> = implementation::Alias {